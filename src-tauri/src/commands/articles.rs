@@ -0,0 +1,276 @@
+// src/commands/articles.rs
+use crate::app_state::AppState;
+use crate::db::{ensure_schema, open_db};
+use crate::models::article::{Article, ArticleInput};
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+
+fn article_from_row(row: &Row) -> rusqlite::Result<Article> {
+    Ok(Article {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        sku: row.get(2)?,
+        default_price: row.get(3)?,
+        default_description: row.get(4)?,
+        active: row.get(5)?,
+    })
+}
+
+// Trim text fields and reject what the table constraints would reject anyway
+fn clean_input(input: ArticleInput) -> Result<ArticleInput, String> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Article name is required".to_string());
+    }
+    if matches!(input.default_price, Some(p) if p < 0) {
+        return Err("Default price cannot be negative".to_string());
+    }
+    let non_empty = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    Ok(ArticleInput {
+        name,
+        sku: non_empty(input.sku),
+        default_price: input.default_price,
+        default_description: non_empty(input.default_description),
+        active: input.active,
+    })
+}
+
+#[tauri::command]
+pub fn list_articles(
+    state: tauri::State<AppState>,
+    include_inactive: Option<bool>,
+) -> Result<Vec<Article>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, name, sku, default_price, default_description, active
+            FROM articles
+            WHERE ?1 OR active = 1
+            ORDER BY active DESC, name ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![include_inactive.unwrap_or(false)], article_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_article(state: tauri::State<AppState>, id: i64) -> Result<Article, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        r#"
+        SELECT id, name, sku, default_price, default_description, active
+        FROM articles
+        WHERE id = ?1
+        "#,
+        [id],
+        article_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Article {id} not found"))
+}
+
+#[tauri::command]
+pub fn create_article(state: tauri::State<AppState>, article: ArticleInput) -> Result<i64, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let a = clean_input(article)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    tx.execute(
+        r#"
+        INSERT INTO articles (name, sku, default_price, default_description, active)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![
+            a.name,
+            a.sku,
+            a.default_price,
+            a.default_description,
+            a.active.unwrap_or(true)
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+
+    // Historical items typed with this name now belong to the catalog entry
+    tx.execute(
+        r#"
+        UPDATE order_items SET article_id = ?1
+        WHERE article_id IS NULL AND TRIM(article_name) = ?2 COLLATE NOCASE
+        "#,
+        params![id, a.name],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Existing order items keep the name they were sold under; only the link follows the rename.
+#[tauri::command]
+pub fn update_article(
+    state: tauri::State<AppState>,
+    id: i64,
+    article: ArticleInput,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let a = clean_input(article)?;
+    let changed = conn
+        .execute(
+            r#"
+            UPDATE articles SET
+              name = ?1,
+              sku = ?2,
+              default_price = ?3,
+              default_description = ?4,
+              active = COALESCE(?5, active),
+              updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE id = ?6
+            "#,
+            params![a.name, a.sku, a.default_price, a.default_description, a.active, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Article {id} not found"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_article_active(
+    state: tauri::State<AppState>,
+    id: i64,
+    active: bool,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+    let changed = conn
+        .execute(
+            r#"
+            UPDATE articles
+            SET active = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE id = ?2
+            "#,
+            params![active, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Article {id} not found"));
+    }
+    Ok(())
+}
+
+/// Removes the catalog entry only; order items keep their article name as plain text.
+/// Articles with stock movements are refused so the ledger keeps its history;
+/// deactivate those instead.
+#[tauri::command]
+pub fn delete_article(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    remove_article(&tx, id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Delete body (caller owns the transaction)
+fn remove_article(conn: &Connection, id: i64) -> Result<(), String> {
+    let name: String = conn
+        .query_row(r#"SELECT name FROM articles WHERE id = ?1"#, [id], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Article {id} not found"))?;
+    let has_movements: bool = conn
+        .query_row(
+            r#"SELECT EXISTS(SELECT 1 FROM stock_movements WHERE article_id = ?1)"#,
+            [id],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_movements {
+        return Err(format!("'{name}' has stock movements and cannot be deleted; deactivate it instead"));
+    }
+
+    conn.execute(r#"UPDATE order_items SET article_id = NULL WHERE article_id = ?1"#, params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(r#"DELETE FROM articles WHERE id = ?1"#, params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(name: &str) -> ArticleInput {
+        ArticleInput { name: name.into(), sku: None, default_price: None, default_description: None, active: None }
+    }
+
+    #[test]
+    fn clean_input_trims_and_drops_blank_optionals() {
+        let a = clean_input(ArticleInput {
+            sku: Some("  ".into()),
+            default_description: Some(" Blue mug ".into()),
+            default_price: Some(0),
+            ..input("  Mug ")
+        })
+        .unwrap();
+        assert_eq!(a.name, "Mug");
+        assert_eq!(a.sku, None);
+        assert_eq!(a.default_description.as_deref(), Some("Blue mug"));
+        assert_eq!(a.default_price, Some(0));
+    }
+
+    #[test]
+    fn clean_input_rejects_blank_names_and_negative_prices() {
+        assert_eq!(clean_input(input(" ")).unwrap_err(), "Article name is required");
+        let priced = ArticleInput { default_price: Some(-5), ..input("Mug") };
+        assert_eq!(clean_input(priced).unwrap_err(), "Default price cannot be negative");
+    }
+
+    #[test]
+    fn articles_with_stock_history_are_kept() {
+        let conn = crate::db::open_test_db();
+        let add = |name: &str| -> i64 {
+            conn.execute("INSERT INTO articles (name) VALUES (?1)", [name]).unwrap();
+            conn.last_insert_rowid()
+        };
+        let mug = add("Mug");
+        let cap = add("Cap");
+        conn.execute(
+            "INSERT INTO stock_movements (article_id, kind, quantity) VALUES (?1, 'receipt', 5)",
+            [mug],
+        )
+        .unwrap();
+
+        assert_eq!(
+            remove_article(&conn, mug),
+            Err("'Mug' has stock movements and cannot be deleted; deactivate it instead".to_string())
+        );
+        let movements: i64 =
+            conn.query_row("SELECT COUNT(*) FROM stock_movements WHERE article_id = ?1", [mug], |r| r.get(0)).unwrap();
+        assert_eq!(movements, 1);
+
+        assert_eq!(remove_article(&conn, cap), Ok(()));
+        assert_eq!(remove_article(&conn, cap), Err(format!("Article {cap} not found")));
+    }
+}
@@ -0,0 +1,256 @@
+// src/commands/bulk.rs
+// Bulk versions of the order mutations. The selection is a list of ids or an
+// `OrderFilter`; everything runs in one transaction, with a savepoint per order
+// so one failure (e.g. an illegal status move) leaves the others applied.
+use crate::app_state::AppState;
+use crate::commands::journal::{capture_order_state, record_batch_change};
+use crate::commands::opened_orders::append_opened_order;
+use crate::commands::orders::{change_status, order_filter_sql, trash_order};
+use crate::db::{ensure_schema, get_or_create_delivery_company, open_db, record_order_event};
+use crate::models::orders::{BulkItemResult, BulkOutcome, BulkReport, OrderFilter, OrderStatus, StatusError};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, TransactionBehavior};
+use serde_json::json;
+
+const MAX_BULK_ORDERS: usize = 1000;
+
+// Ids to act on, in the given order without duplicates, or every match of the filter
+fn selected_ids(conn: &Connection, ids: Option<Vec<i64>>, filter: Option<OrderFilter>) -> Result<Vec<i64>, String> {
+    let out = match (ids, filter) {
+        (Some(ids), None) => {
+            let mut out: Vec<i64> = Vec::with_capacity(ids.len());
+            for id in ids {
+                if !out.contains(&id) {
+                    out.push(id);
+                }
+            }
+            out
+        }
+        (None, Some(filter)) => {
+            let (where_sql, args) = order_filter_sql(conn, &filter)?;
+            let mut stmt = conn
+                .prepare(&format!("SELECT o.id FROM active_orders o WHERE {where_sql} ORDER BY o.id"))
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params_from_iter(args.iter()), |r| r.get::<_, i64>(0))
+                .map_err(|e| e.to_string())?;
+            let mut out = Vec::new();
+            for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+            out
+        }
+        _ => return Err("Pass either a list of order ids or a filter".to_string()),
+    };
+    if out.len() > MAX_BULK_ORDERS {
+        return Err(format!(
+            "{} orders selected; bulk changes are limited to {MAX_BULK_ORDERS} at a time",
+            out.len()
+        ));
+    }
+    Ok(out)
+}
+
+// Apply `op` to every id; `op` returns whether it changed anything.
+// Journaled actions are one undo step covering every changed order.
+fn run_bulk<F>(
+    conn: &mut Connection,
+    ids: &[i64],
+    action: Option<&str>,
+    op: F,
+) -> Result<BulkReport, String>
+where
+    F: Fn(&Connection, i64) -> Result<bool, String>,
+{
+    let mut tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let mut report = BulkReport { changed: 0, unchanged: 0, failed: 0, results: Vec::with_capacity(ids.len()) };
+    let mut batch: Option<i64> = None;
+    for &id in ids {
+        let sp = tx.savepoint().map_err(|e| e.to_string())?;
+        let applied = (|| {
+            let before = capture_order_state(&sp, id).map_err(|e| e.to_string())?;
+            let changed = op(&sp, id)?;
+            let mut journaled = batch;
+            if let (true, Some(action)) = (changed, action) {
+                journaled = record_batch_change(&sp, batch, id, action, before).map_err(|e| e.to_string())?;
+            }
+            Ok::<(bool, Option<i64>), String>((changed, journaled))
+        })();
+        let (outcome, error) = match applied {
+            Ok((changed, journaled)) => {
+                sp.commit().map_err(|e| e.to_string())?;
+                batch = journaled;
+                if changed {
+                    report.changed += 1;
+                    (BulkOutcome::Changed, None)
+                } else {
+                    report.unchanged += 1;
+                    (BulkOutcome::Unchanged, None)
+                }
+            }
+            // Dropping the savepoint rolls this order back
+            Err(e) => {
+                report.failed += 1;
+                (BulkOutcome::Failed, Some(e))
+            }
+        };
+        report.results.push(BulkItemResult { id, outcome, error });
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+fn ensure_active(conn: &Connection, id: i64) -> Result<(), String> {
+    let found: bool = conn
+        .query_row(r#"SELECT EXISTS(SELECT 1 FROM active_orders WHERE id = ?1)"#, [id], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    if !found {
+        return Err(format!("Order {id} not found"));
+    }
+    Ok(())
+}
+
+/// Move the selected orders to `status`, following the same lifecycle rules as
+/// `set_order_status`; orders that cannot make the move are reported as failed.
+#[tauri::command]
+pub fn bulk_set_status(
+    state: tauri::State<AppState>,
+    ids: Option<Vec<i64>>,
+    filter: Option<OrderFilter>,
+    status: String,
+) -> Result<BulkReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let next = OrderStatus::parse(&status).ok_or_else(|| StatusError::Unknown(status.clone()).to_string())?;
+    let ids = selected_ids(&conn, ids, filter)?;
+    run_bulk(&mut conn, &ids, Some("bulk_set_status"), |tx, id| change_status(tx, id, next))
+}
+
+/// Hand the selected orders to another delivery company.
+/// Fees and dates are kept as they are; edit an order to re-price it.
+#[tauri::command]
+pub fn bulk_set_delivery_company(
+    state: tauri::State<AppState>,
+    ids: Option<Vec<i64>>,
+    filter: Option<OrderFilter>,
+    delivery_company: String,
+) -> Result<BulkReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    if delivery_company.trim().is_empty() {
+        return Err("Delivery company is required".to_string());
+    }
+    let ids = selected_ids(&conn, ids, filter)?;
+
+    run_bulk(&mut conn, &ids, Some("bulk_set_delivery_company"), |tx, id| {
+        let before: (String, Option<i64>) = tx
+            .query_row(
+                r#"SELECT delivery_company, delivery_company_id FROM active_orders WHERE id = ?1"#,
+                [id],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Order {id} not found"))?;
+        // Created by the first order that moves and rolled back with it, so a run
+        // where every order fails leaves no new company behind
+        let (company_id, company_name) =
+            get_or_create_delivery_company(tx, &delivery_company).map_err(|e| e.to_string())?;
+        if before == (company_name.clone(), Some(company_id)) {
+            return Ok(false);
+        }
+        tx.execute(
+            r#"UPDATE orders SET delivery_company = ?1, delivery_company_id = ?2 WHERE id = ?3"#,
+            params![company_name, company_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        record_order_event(
+            tx,
+            id,
+            "edited",
+            Some(&json!({ "deliveryCompany": before.0 }).to_string()),
+            Some(&json!({ "deliveryCompany": company_name }).to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(true)
+    })
+}
+
+/// Move the delivery date (yyyy-mm-dd) of the selected orders.
+#[tauri::command]
+pub fn bulk_reschedule_orders(
+    state: tauri::State<AppState>,
+    ids: Option<Vec<i64>>,
+    filter: Option<OrderFilter>,
+    delivery_date: String,
+) -> Result<BulkReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let day: String = conn
+        .query_row(r#"SELECT date(?1)"#, [delivery_date.trim()], |r| r.get::<_, Option<String>>(0))
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invalid date '{}'", delivery_date.trim()))?;
+    let ids = selected_ids(&conn, ids, filter)?;
+
+    run_bulk(&mut conn, &ids, Some("bulk_reschedule_orders"), |tx, id| {
+        let before: String = tx
+            .query_row(r#"SELECT delivery_date FROM active_orders WHERE id = ?1"#, [id], |r| r.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Order {id} not found"))?;
+        if before == day {
+            return Ok(false);
+        }
+        tx.execute(r#"UPDATE orders SET delivery_date = ?1 WHERE id = ?2"#, params![day, id])
+            .map_err(|e| e.to_string())?;
+        record_order_event(
+            tx,
+            id,
+            "edited",
+            Some(&json!({ "deliveryDate": before }).to_string()),
+            Some(&json!({ "deliveryDate": day }).to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(true)
+    })
+}
+
+/// Move the selected orders to the trash.
+#[tauri::command]
+pub fn bulk_delete_orders(
+    state: tauri::State<AppState>,
+    ids: Option<Vec<i64>>,
+    filter: Option<OrderFilter>,
+) -> Result<BulkReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let ids = selected_ids(&conn, ids, filter)?;
+    run_bulk(&mut conn, &ids, Some("bulk_delete_orders"), |tx, id| {
+        if !trash_order(tx, id).map_err(|e| e.to_string())? {
+            return Err(format!("Order {id} not found"));
+        }
+        Ok(true)
+    })
+}
+
+/// Add the selected orders to the opened bar, after the ones already there.
+#[tauri::command]
+pub fn bulk_open_orders(
+    state: tauri::State<AppState>,
+    ids: Option<Vec<i64>>,
+    filter: Option<OrderFilter>,
+) -> Result<BulkReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let ids = selected_ids(&conn, ids, filter)?;
+    run_bulk(&mut conn, &ids, None, |tx, id| {
+        ensure_active(tx, id)?;
+        append_opened_order(tx, id).map_err(|e| e.to_string())
+    })
+}
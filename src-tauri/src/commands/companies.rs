@@ -1,37 +1,174 @@
 // src/commands/companies
 use crate::app_state::AppState;
-use crate::db::{ensure_schema, open_db};
-use crate::models::company::DeliveryCompany;
-use rusqlite::{params};
+use crate::commands::orders::status_reached_cte;
+use crate::db::{default_country_code, ensure_schema, open_db};
+use crate::models::company::{
+    CompanyRate, CompanyRateCard, CompanyRateInput, CompanyScorecard, DeliveryCompany,
+    DeliveryCompanyDetailsInput, DeliverySuggestion,
+};
+use crate::models::orders::OrderStatus;
+use crate::phone::normalize_phone;
+use crate::util::{render_tracking_url, TRACKING_PLACEHOLDER};
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+
+const COMPANY_SELECT: &str = r#"
+    SELECT id, name, active, contact_name, phone, email, notes,
+           pickup_schedule, account_number, tracking_url_template
+    FROM delivery_companies
+"#;
+
+fn company_from_row(row: &Row) -> rusqlite::Result<DeliveryCompany> {
+    Ok(DeliveryCompany {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        active: row.get(2)?,
+        contact_name: row.get(3)?,
+        phone: row.get(4)?,
+        email: row.get(5)?,
+        notes: row.get(6)?,
+        pickup_schedule: row.get(7)?,
+        account_number: row.get(8)?,
+        tracking_url_template: row.get(9)?,
+    })
+}
 
 #[tauri::command]
 pub fn list_delivery_companies(state: tauri::State<AppState>) -> Result<Vec<DeliveryCompany>, String> {
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
-    let mut stmt = conn
-        .prepare(
+    let sql = format!("{COMPANY_SELECT} ORDER BY active DESC, name ASC");
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], company_from_row).map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_delivery_company(
+    state: tauri::State<AppState>,
+    id: i64,
+) -> Result<DeliveryCompany, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let sql = format!("{COMPANY_SELECT} WHERE id = ?1");
+    conn.query_row(&sql, [id], company_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Delivery company {id} not found"))
+}
+
+/// Replace the contact and integration details of a company (empty fields are cleared).
+#[tauri::command]
+pub fn update_delivery_company_details(
+    state: tauri::State<AppState>,
+    id: i64,
+    details: DeliveryCompanyDetailsInput,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let non_empty = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+    let phone = match non_empty(details.phone) {
+        Some(p) => {
+            let cc = default_country_code(&conn).map_err(|e| e.to_string())?;
+            Some(normalize_phone(&p, &cc).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    let email = non_empty(details.email);
+    if let Some(e) = &email {
+        let valid = e.split_once('@').is_some_and(|(user, domain)| {
+            !user.is_empty() && domain.contains('.') && !e.contains(char::is_whitespace)
+        });
+        if !valid {
+            return Err(format!("Invalid email address '{e}'"));
+        }
+    }
+    let template = non_empty(details.tracking_url_template);
+    if let Some(t) = &template {
+        if !(t.starts_with("https://") || t.starts_with("http://")) {
+            return Err("Tracking URL template must start with http:// or https://".to_string());
+        }
+        if !t.contains(TRACKING_PLACEHOLDER) {
+            return Err(format!("Tracking URL template must contain {TRACKING_PLACEHOLDER}"));
+        }
+    }
+
+    let changed = conn
+        .execute(
             r#"
-            SELECT id, name, active
-            FROM delivery_companies
-            ORDER BY active DESC, name ASC
+            UPDATE delivery_companies SET
+              contact_name = ?1,
+              phone = ?2,
+              email = ?3,
+              notes = ?4,
+              pickup_schedule = ?5,
+              account_number = ?6,
+              tracking_url_template = ?7
+            WHERE id = ?8
             "#,
+            params![
+                non_empty(details.contact_name),
+                phone,
+                email,
+                non_empty(details.notes),
+                non_empty(details.pickup_schedule),
+                non_empty(details.account_number),
+                template,
+                id
+            ],
         )
         .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Delivery company {id} not found"));
+    }
+    Ok(())
+}
 
-    let rows = stmt
-        .query_map([], |row| {
-            Ok(DeliveryCompany {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                active: row.get(2)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
+// Courier tracking link for a tracking number; None without a template or number
+pub(crate) fn tracking_link(
+    conn: &Connection,
+    company_id: i64,
+    tracking_number: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    if tracking_number.trim().is_empty() {
+        return Ok(None);
+    }
+    let template: Option<String> = conn
+        .query_row(
+            r#"SELECT tracking_url_template FROM delivery_companies WHERE id = ?1"#,
+            [company_id],
+            |r| r.get(0),
+        )
+        .optional()?
+        .flatten();
+    Ok(template.map(|t| render_tracking_url(&t, tracking_number)))
+}
 
-    let mut out = Vec::new();
-    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
-    Ok(out)
+/// Tracking link for an order, from its courier's template and its tracking number.
+#[tauri::command]
+pub fn get_tracking_link(state: tauri::State<AppState>, order_id: i64) -> Result<Option<String>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (company_id, tracking_number): (Option<i64>, Option<String>) = conn
+        .query_row(
+            r#"SELECT delivery_company_id, tracking_number FROM active_orders WHERE id = ?1"#,
+            [order_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Order {order_id} not found"))?;
+    match (company_id, tracking_number) {
+        (Some(cid), Some(tn)) => tracking_link(&conn, cid, &tn).map_err(|e| e.to_string()),
+        _ => Ok(None),
+    }
 }
 
 #[tauri::command]
@@ -74,9 +211,439 @@ pub fn rename_delivery_company(
 ) -> Result<(), String> {
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    // Renaming onto another company's name would hit UNIQUE NOCASE; point to the merge instead
+    let clash: Option<i64> = conn
+        .query_row(
+            r#"SELECT id FROM delivery_companies WHERE name = TRIM(?1) COLLATE NOCASE AND id <> ?2"#,
+            params![new_name, id],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(other) = clash {
+        return Err(format!(
+            "Delivery company '{}' already exists (id {other}); merge the two instead",
+            new_name.trim()
+        ));
+    }
+
     conn.execute(
         r#"UPDATE delivery_companies SET name = TRIM(?1) WHERE id = ?2"#,
         params![new_name, id],
     ).map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// Fold spelling variants into `target_id`: orders, rates and settlements move over,
+/// the merge is recorded and the sources are removed. Returns the number of orders moved.
+#[tauri::command]
+pub fn merge_delivery_companies(
+    state: tauri::State<AppState>,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<i64, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let target_exists: bool = tx
+        .query_row("SELECT 1 FROM delivery_companies WHERE id = ?1", [target_id], |_| Ok(true))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if !target_exists {
+        return Err(format!("Delivery company {target_id} not found"));
+    }
+
+    // A company listed twice would otherwise be "not found" on its second pass
+    let mut source_ids = source_ids;
+    source_ids.sort_unstable();
+    source_ids.dedup();
+
+    let mut moved = 0_i64;
+    for sid in source_ids.iter().copied().filter(|sid| *sid != target_id) {
+        let source_name: String = tx
+            .query_row("SELECT name FROM delivery_companies WHERE id = ?1", [sid], |r| r.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Delivery company {sid} not found"))?;
+
+        // tr_orders_set_company_text rewrites orders.delivery_company from the new id
+        let n = tx
+            .execute(
+                "UPDATE orders SET delivery_company_id = ?1 WHERE delivery_company_id = ?2",
+                params![target_id, sid],
+            )
+            .map_err(|e| e.to_string())? as i64;
+        tx.execute(
+            "UPDATE settlements SET delivery_company_id = ?1 WHERE delivery_company_id = ?2",
+            params![target_id, sid],
+        )
+        .map_err(|e| e.to_string())?;
+        // The target's own rate wins for cities both companies list
+        tx.execute(
+            "UPDATE OR IGNORE company_rates SET delivery_company_id = ?1 WHERE delivery_company_id = ?2",
+            params![target_id, sid],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            r#"
+            INSERT INTO company_merges (source_id, source_name, target_id, orders_moved)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![sid, source_name, target_id, n],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM delivery_companies WHERE id = ?1", params![sid])
+            .map_err(|e| e.to_string())?;
+        moved += n;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(moved)
+}
+
+// Fee, promised date and coverage for an order going to `city` with this company.
+// A listed city uses its own rate; other cities get the company defaults,
+// unless the company only serves the cities it lists.
+pub(crate) fn delivery_terms(
+    conn: &Connection,
+    company_id: Option<i64>,
+    city: &str,
+) -> Result<DeliverySuggestion, rusqlite::Error> {
+    let mut out = DeliverySuggestion {
+        delivery_company_id: company_id,
+        fee: None,
+        delivery_days: None,
+        delivery_date: None,
+        serves_city: true,
+        warning: None,
+    };
+    let Some(company_id) = company_id else { return Ok(out) };
+
+    let company: Option<(String, Option<i64>, Option<i64>, bool)> = conn
+        .query_row(
+            r#"
+            SELECT name, default_fee, default_delivery_days, restrict_to_listed_cities
+            FROM delivery_companies
+            WHERE id = ?1
+            "#,
+            [company_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .optional()?;
+    let Some((name, default_fee, default_days, restricted)) = company else { return Ok(out) };
+
+    let rate: Option<(i64, Option<i64>)> = conn
+        .query_row(
+            r#"
+            SELECT fee, delivery_days FROM company_rates
+            WHERE delivery_company_id = ?1 AND city = TRIM(?2) COLLATE NOCASE
+            "#,
+            params![company_id, city],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()?;
+
+    match rate {
+        Some((fee, days)) => {
+            out.fee = Some(fee);
+            out.delivery_days = days.or(default_days);
+        }
+        None if restricted => {
+            out.serves_city = false;
+            out.warning = Some(format!("{name} does not deliver to {}", city.trim()));
+        }
+        None => {
+            out.fee = default_fee;
+            out.delivery_days = default_days;
+        }
+    }
+
+    if let Some(days) = out.delivery_days {
+        out.delivery_date = Some(conn.query_row(
+            r#"SELECT date('now','localtime', printf('+%d days', ?1))"#,
+            [days],
+            |r| r.get(0),
+        )?);
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_company_rate_card(
+    state: tauri::State<AppState>,
+    id: i64,
+) -> Result<CompanyRateCard, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (default_fee, default_delivery_days, restrict_to_listed_cities) = conn
+        .query_row(
+            r#"
+            SELECT default_fee, default_delivery_days, restrict_to_listed_cities
+            FROM delivery_companies
+            WHERE id = ?1
+            "#,
+            [id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Delivery company {id} not found"))?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, city, fee, delivery_days
+            FROM company_rates
+            WHERE delivery_company_id = ?1
+            ORDER BY city ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([id], |row| {
+            Ok(CompanyRate {
+                id: row.get(0)?,
+                city: row.get(1)?,
+                fee: row.get(2)?,
+                delivery_days: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut rates = Vec::new();
+    for r in rows { rates.push(r.map_err(|e| e.to_string())?); }
+
+    Ok(CompanyRateCard {
+        delivery_company_id: id,
+        default_fee,
+        default_delivery_days,
+        restrict_to_listed_cities,
+        rates,
+    })
+}
+
+#[tauri::command]
+pub fn set_company_delivery_defaults(
+    state: tauri::State<AppState>,
+    id: i64,
+    default_fee: Option<i64>,
+    default_delivery_days: Option<i64>,
+    restrict_to_listed_cities: Option<bool>,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    if matches!(default_fee, Some(f) if f < 0) {
+        return Err("Default fee cannot be negative".to_string());
+    }
+    if matches!(default_delivery_days, Some(d) if d < 0) {
+        return Err("Delivery days cannot be negative".to_string());
+    }
+    let changed = conn
+        .execute(
+            r#"
+            UPDATE delivery_companies SET
+              default_fee = ?1,
+              default_delivery_days = ?2,
+              restrict_to_listed_cities = COALESCE(?3, restrict_to_listed_cities)
+            WHERE id = ?4
+            "#,
+            params![default_fee, default_delivery_days, restrict_to_listed_cities, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Delivery company {id} not found"));
+    }
+    Ok(())
+}
+
+/// Add a city to the company's rate card, or update its fee/delivery days.
+#[tauri::command]
+pub fn upsert_company_rate(
+    state: tauri::State<AppState>,
+    delivery_company_id: i64,
+    rate: CompanyRateInput,
+) -> Result<i64, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let city = rate.city.trim();
+    if city.is_empty() {
+        return Err("City is required".to_string());
+    }
+    if rate.fee < 0 {
+        return Err("Fee cannot be negative".to_string());
+    }
+    if matches!(rate.delivery_days, Some(d) if d < 0) {
+        return Err("Delivery days cannot be negative".to_string());
+    }
+    conn.query_row(
+        r#"
+        INSERT INTO company_rates (delivery_company_id, city, fee, delivery_days)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(delivery_company_id, city) DO UPDATE SET
+          fee = excluded.fee,
+          delivery_days = excluded.delivery_days
+        RETURNING id
+        "#,
+        params![delivery_company_id, city, rate.fee, rate.delivery_days],
+        |r| r.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_company_rate(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+    conn.execute(r#"DELETE FROM company_rates WHERE id = ?1"#, params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Prefill for the order form when the company or city changes; never creates the company.
+#[tauri::command]
+pub fn suggest_delivery(
+    state: tauri::State<AppState>,
+    delivery_company: String,
+    city: String,
+) -> Result<DeliverySuggestion, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let company_id: Option<i64> = conn
+        .query_row(
+            r#"SELECT id FROM delivery_companies WHERE name = TRIM(?1) COLLATE NOCASE"#,
+            params![delivery_company],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    delivery_terms(&conn, company_id, &city).map_err(|e| e.to_string())
+}
+
+/// Per-company delivery performance between two local dates (inclusive).
+/// Defaults to the last 90 days; volume is compared with the period just before.
+#[tauri::command]
+pub fn get_company_scorecards(
+    state: tauri::State<AppState>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<Vec<CompanyScorecard>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let date_from = date_from.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    let date_to = date_to.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    for d in date_from.iter().chain(date_to.iter()) {
+        let valid: bool = conn
+            .query_row(r#"SELECT date(?1) IS NOT NULL"#, [d], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        if !valid {
+            return Err(format!("Invalid date '{d}': expected yyyy-mm-dd"));
+        }
+    }
+
+    let delivered = status_reached_cte("delivered", "delivered_at", OrderStatus::Delivered);
+    let shipped = status_reached_cte("shipped", "shipped_at", OrderStatus::WithCourier);
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+            WITH bounds AS (
+              SELECT COALESCE(date(?1), date('now','localtime','-89 days')) AS d_from,
+                     COALESCE(date(?2), date('now','localtime')) AS d_to
+            ),
+            b AS (
+              SELECT d_from, d_to,
+                     date(d_from, printf('-%d days', CAST(julianday(d_to) - julianday(d_from) + 1 AS INTEGER))) AS p_from,
+                     date(d_from, '-1 day') AS p_to
+              FROM bounds
+            ),
+            {delivered},
+            {shipped},
+            created AS (
+              SELECT o.delivery_company_id AS cid,
+                     COUNT(*) AS orders,
+                     SUM(o.status = 'returned') AS returned,
+                     SUM(o.status = 'cancelled') AS cancelled,
+                     SUM(o.status IN ('delivered','returned')) AS reached
+              FROM active_orders o, b
+              WHERE date(o.created_at, 'localtime') BETWEEN b.d_from AND b.d_to
+              GROUP BY o.delivery_company_id
+            ),
+            prev AS (
+              SELECT o.delivery_company_id AS cid, COUNT(*) AS orders
+              FROM active_orders o, b
+              WHERE date(o.created_at, 'localtime') BETWEEN b.p_from AND b.p_to
+              GROUP BY o.delivery_company_id
+            ),
+            deliv AS (
+              SELECT o.delivery_company_id AS cid,
+                     COUNT(*) AS delivered,
+                     SUM(date(d.delivered_at, 'localtime') <= date(o.delivery_date)) AS on_time,
+                     AVG(CASE WHEN date(d.delivered_at, 'localtime') > date(o.delivery_date)
+                              THEN julianday(date(d.delivered_at, 'localtime')) - julianday(date(o.delivery_date))
+                         END) AS avg_late,
+                     AVG(julianday(datetime(d.delivered_at))
+                         - julianday(datetime(COALESCE(s.shipped_at, o.created_at)))) AS transit
+              FROM delivered d
+              JOIN active_orders o ON o.id = d.order_id
+              LEFT JOIN shipped s ON s.order_id = d.order_id, b
+              WHERE date(d.delivered_at, 'localtime') BETWEEN b.d_from AND b.d_to
+              GROUP BY o.delivery_company_id
+            )
+            SELECT dc.id, dc.name, dc.active,
+                   COALESCE(c.orders, 0), COALESCE(p.orders, 0), COALESCE(dv.delivered, 0),
+                   ROUND(100.0 * dv.on_time / NULLIF(dv.delivered, 0), 1),
+                   ROUND(dv.avg_late, 2),
+                   ROUND(dv.transit, 2),
+                   COALESCE(c.returned, 0), COALESCE(c.cancelled, 0),
+                   ROUND(100.0 * c.returned / NULLIF(c.reached, 0), 1),
+                   ROUND(100.0 * c.cancelled / NULLIF(c.orders, 0), 1)
+            FROM delivery_companies dc
+            LEFT JOIN created c ON c.cid = dc.id
+            LEFT JOIN prev p ON p.cid = dc.id
+            LEFT JOIN deliv dv ON dv.cid = dc.id
+            ORDER BY dc.active DESC, COALESCE(c.orders, 0) DESC, dc.name ASC
+            "#
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![date_from, date_to], |row| {
+            let orders: i64 = row.get(3)?;
+            let previous_orders: i64 = row.get(4)?;
+            let volume_change_pct = (previous_orders > 0).then(|| {
+                let pct = 100.0 * (orders - previous_orders) as f64 / previous_orders as f64;
+                (pct * 10.0).round() / 10.0
+            });
+            Ok(CompanyScorecard {
+                delivery_company_id: row.get(0)?,
+                name: row.get(1)?,
+                active: row.get(2)?,
+                orders,
+                previous_orders,
+                volume_change_pct,
+                delivered: row.get(5)?,
+                on_time_pct: row.get(6)?,
+                avg_days_late: row.get(7)?,
+                avg_transit_days: row.get(8)?,
+                returned: row.get(9)?,
+                cancelled: row.get(10)?,
+                return_rate_pct: row.get(11)?,
+                cancel_rate_pct: row.get(12)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
@@ -0,0 +1,294 @@
+// src/commands/customers.rs
+use crate::app_state::AppState;
+use crate::commands::orders::{order_list_item_from_row, ORDER_LIST_SELECT};
+use crate::db::{default_country_code, ensure_schema, open_db};
+use crate::models::customer::{Customer, CustomerAutofill, CustomerDetail, CustomerStats};
+use crate::phone::normalize_phone;
+use crate::util::{escape_like, phone_key};
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+
+const CUSTOMER_SELECT: &str = r#"
+    SELECT c.id, c.phone, c.name, c.city, c.address,
+           (SELECT COUNT(*) FROM active_orders o WHERE o.customer_id = c.id) AS order_count,
+           (SELECT MAX(o.created_at) FROM active_orders o WHERE o.customer_id = c.id) AS last_order_at
+    FROM customers c
+"#;
+
+fn customer_from_row(row: &Row) -> rusqlite::Result<Customer> {
+    Ok(Customer {
+        id: row.get(0)?,
+        phone: row.get(1)?,
+        name: row.get(2)?,
+        city: row.get(3)?,
+        address: row.get(4)?,
+        order_count: row.get(5)?,
+        last_order_at: row.get(6)?,
+    })
+}
+
+#[tauri::command]
+pub fn list_customers(state: tauri::State<AppState>) -> Result<Vec<Customer>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let sql = format!("{CUSTOMER_SELECT} ORDER BY last_order_at DESC, c.id DESC");
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], customer_from_row).map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn search_customers(
+    state: tauri::State<AppState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<Customer>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let text_pat = format!("%{}%", escape_like(query.trim()));
+    // Phone fragments match on digits only, whatever separators were typed;
+    // a national trunk "0" is dropped since stored numbers are E.164
+    let key = phone_key(&query);
+    let digits = key.trim_start_matches('+').trim_start_matches('0');
+    let phone_pat = if digits.is_empty() { None } else { Some(format!("%{digits}%")) };
+    let lim = limit.unwrap_or(10).max(1);
+
+    let sql = format!(
+        "{CUSTOMER_SELECT}
+         WHERE c.name LIKE ?1 ESCAPE '\\' OR c.city LIKE ?1 ESCAPE '\\'
+            OR (?2 IS NOT NULL AND c.phone LIKE ?2)
+         ORDER BY order_count DESC, last_order_at DESC
+         LIMIT ?3"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![text_pat, phone_pat, lim], customer_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_customer(state: tauri::State<AppState>, id: i64) -> Result<CustomerDetail, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let sql = format!("{CUSTOMER_SELECT} WHERE c.id = ?1");
+    let customer = conn
+        .query_row(&sql, [id], customer_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Customer {id} not found"))?;
+
+    let stats = conn
+        .query_row(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(SUM(status IN ('pending','confirmed','with_courier')), 0),
+                   COALESCE(SUM(status = 'delivered'), 0),
+                   COALESCE(SUM(status = 'returned'), 0),
+                   COALESCE(SUM(status = 'cancelled'), 0),
+                   COALESCE((SELECT SUM(i.quantity) FROM order_items i
+                             JOIN active_orders o2 ON o2.id = i.order_id
+                             WHERE o2.customer_id = ?1), 0),
+                   MIN(created_at),
+                   MAX(created_at)
+            FROM active_orders
+            WHERE customer_id = ?1
+            "#,
+            [id],
+            |r| {
+                Ok(CustomerStats {
+                    order_count: r.get(0)?,
+                    open_count: r.get(1)?,
+                    delivered_count: r.get(2)?,
+                    returned_count: r.get(3)?,
+                    cancelled_count: r.get(4)?,
+                    total_units: r.get(5)?,
+                    first_order_at: r.get(6)?,
+                    last_order_at: r.get(7)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{ORDER_LIST_SELECT}
+         FROM active_orders o
+         WHERE o.customer_id = ?1
+         ORDER BY o.created_at DESC, o.id DESC"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([id], order_list_item_from_row)
+        .map_err(|e| e.to_string())?;
+    let mut orders = Vec::new();
+    for r in rows { orders.push(r.map_err(|e| e.to_string())?); }
+
+    Ok(CustomerDetail { customer, stats, orders })
+}
+
+/// Look up a customer by phone so the order form can prefill name and last known address.
+#[tauri::command]
+pub fn find_customer_by_phone(
+    state: tauri::State<AppState>,
+    phone: String,
+) -> Result<Option<CustomerAutofill>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let cc = default_country_code(&conn).map_err(|e| e.to_string())?;
+    let Ok(key) = normalize_phone(&phone, &cc) else { return Ok(None) };
+
+    conn.query_row(
+        r#"
+        SELECT id, name, city, address FROM customers
+        WHERE phone = ?1
+           OR id = (SELECT customer_id FROM customer_phone_aliases WHERE phone = ?1)
+        "#,
+        params![key],
+        |r| {
+            Ok(CustomerAutofill {
+                id: r.get(0)?,
+                name: r.get(1)?,
+                city: r.get(2)?,
+                address: r.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Fold duplicate customers into `target_id`: their orders move over and the sources are removed.
+/// Their phone numbers stay linked to the target, so later orders from them land there too.
+#[tauri::command]
+pub fn merge_customers(
+    state: tauri::State<AppState>,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<i64, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let moved = merge_into(&tx, source_ids, target_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(moved)
+}
+
+// Merge body; returns how many orders moved (caller owns the transaction)
+fn merge_into(conn: &Connection, source_ids: Vec<i64>, target_id: i64) -> Result<i64, String> {
+    let target_exists: bool = conn
+        .query_row("SELECT 1 FROM customers WHERE id = ?1", [target_id], |_| Ok(true))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if !target_exists {
+        return Err(format!("Customer {target_id} not found"));
+    }
+    if source_ids.contains(&target_id) {
+        return Err(format!("Customer {target_id} cannot be merged into itself"));
+    }
+
+    let mut source_ids = source_ids;
+    source_ids.sort_unstable();
+    source_ids.dedup();
+
+    let mut moved = 0_i64;
+    for sid in source_ids {
+        let source_phone: String = conn
+            .query_row("SELECT phone FROM customers WHERE id = ?1", [sid], |r| r.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Customer {sid} not found"))?;
+
+        moved += conn
+            .execute(
+                "UPDATE orders SET customer_id = ?1 WHERE customer_id = ?2",
+                params![target_id, sid],
+            )
+            .map_err(|e| e.to_string())? as i64;
+        // Numbers merged into the source earlier follow it
+        conn.execute(
+            "UPDATE customer_phone_aliases SET customer_id = ?1 WHERE customer_id = ?2",
+            params![target_id, sid],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO customer_phone_aliases (phone, customer_id) VALUES (?1, ?2)",
+            params![source_phone, target_id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM customers WHERE id = ?1", params![sid])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::orders::insert_order;
+    use crate::models::orders::NewOrderInput;
+
+    fn order(phone: &str) -> NewOrderInput {
+        serde_json::from_value(serde_json::json!({
+            "clientName": "Sara", "articleName": "Mug", "phone": phone, "city": "Rabat",
+            "address": "1 Rue Test", "deliveryCompany": "Amana", "deliveryDate": "2026-10-20"
+        }))
+        .unwrap()
+    }
+
+    fn customer_of(conn: &Connection, order_id: i64) -> i64 {
+        conn.query_row("SELECT customer_id FROM orders WHERE id = ?1", [order_id], |r| r.get(0)).unwrap()
+    }
+
+    fn customer_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM customers", [], |r| r.get(0)).unwrap()
+    }
+
+    #[test]
+    fn merged_numbers_keep_leading_to_the_target() {
+        let conn = crate::db::open_test_db();
+        let keep = customer_of(&conn, insert_order(&conn, &order("0612345678")).unwrap());
+        let old = insert_order(&conn, &order("0698765432")).unwrap();
+        let source = customer_of(&conn, old);
+
+        assert_eq!(merge_into(&conn, vec![source, source], keep), Ok(1));
+        assert_eq!(customer_of(&conn, old), keep);
+
+        // A new order with the merged-away number does not bring the customer back
+        let again = insert_order(&conn, &order("+212 698-765432")).unwrap();
+        assert_eq!(customer_of(&conn, again), keep);
+        assert_eq!(customer_count(&conn), 1);
+
+        // Merging the target further carries the number along
+        let other = customer_of(&conn, insert_order(&conn, &order("0655555555")).unwrap());
+        merge_into(&conn, vec![keep], other).unwrap();
+        assert_eq!(customer_of(&conn, insert_order(&conn, &order("0698765432")).unwrap()), other);
+        assert_eq!(customer_count(&conn), 1);
+    }
+
+    #[test]
+    fn merge_rejects_unknown_ids_and_self_merges() {
+        let conn = crate::db::open_test_db();
+        let keep = customer_of(&conn, insert_order(&conn, &order("0612345678")).unwrap());
+        let source = customer_of(&conn, insert_order(&conn, &order("0698765432")).unwrap());
+
+        assert_eq!(merge_into(&conn, vec![source], 999), Err("Customer 999 not found".to_string()));
+        assert_eq!(merge_into(&conn, vec![source, 999], keep), Err("Customer 999 not found".to_string()));
+        assert_eq!(
+            merge_into(&conn, vec![source, keep], keep),
+            Err(format!("Customer {keep} cannot be merged into itself"))
+        );
+    }
+}
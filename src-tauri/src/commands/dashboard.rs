@@ -1,8 +1,11 @@
 // src/commands/dashboard.rs
 use crate::app_state::AppState;
-use crate::db::{ensure_schema, open_db};
+use crate::commands::inventory::{count_low_stock, load_stock_levels};
+use crate::commands::orders::status_reached_cte;
+use crate::db::{default_currency, ensure_schema, open_db};
 use crate::models::dashboard::*;
-use rusqlite::OptionalExtension;
+use crate::models::orders::OrderStatus;
+use rusqlite::{params, OptionalExtension};
 
 #[tauri::command]
 pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData, String> {
@@ -10,48 +13,70 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
     // ---- KPIs ----
-    let total_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders", [], |r| r.get(0)).map_err(|e| e.to_string())?;
-    let open_orders: i64 = conn.query_row("SELECT COUNT(*) FROM orders WHERE done = 0", [], |r| r.get(0)).map_err(|e| e.to_string())?;
+    let total_orders: i64 = conn.query_row("SELECT COUNT(*) FROM active_orders", [], |r| r.get(0)).map_err(|e| e.to_string())?;
+    let open_orders: i64 = conn.query_row("SELECT COUNT(*) FROM active_orders WHERE status IN ('pending','confirmed','with_courier')", [], |r| r.get(0)).map_err(|e| e.to_string())?;
     let overdue_open: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE done = 0 AND date(delivery_date) < date('now','localtime')",
+        "SELECT COUNT(*) FROM active_orders WHERE status IN ('pending','confirmed','with_courier') AND date(delivery_date) < date('now','localtime')",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
     let due_today: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE done = 0 AND date(delivery_date) = date('now','localtime')",
+        "SELECT COUNT(*) FROM active_orders WHERE status IN ('pending','confirmed','with_courier') AND date(delivery_date) = date('now','localtime')",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
     let due_next_7: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE done = 0 AND date(delivery_date) > date('now','localtime') AND date(delivery_date) <= date('now','localtime','+7 days')",
+        "SELECT COUNT(*) FROM active_orders WHERE status IN ('pending','confirmed','with_courier') AND date(delivery_date) > date('now','localtime') AND date(delivery_date) <= date('now','localtime','+7 days')",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
+    // Throughput: orders that reached "delivered" in the window, from the status history
+    let delivered = status_reached_cte("delivered", "delivered_at", OrderStatus::Delivered);
     let done_7d: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE done = 1 AND datetime(created_at) >= datetime('now','-7 days')",
+        &format!("WITH {delivered}
+         SELECT COUNT(*) FROM delivered
+         WHERE datetime(delivered_at) >= datetime('now','-7 days')
+           AND order_id IN (SELECT id FROM active_orders)"),
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
     let done_30d: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE done = 1 AND datetime(created_at) >= datetime('now','-30 days')",
+        &format!("WITH {delivered}
+         SELECT COUNT(*) FROM delivered
+         WHERE datetime(delivered_at) >= datetime('now','-30 days')
+           AND order_id IN (SELECT id FROM active_orders)"),
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
+    // Revenue: amount collected on delivered orders, by completion date.
+    // Orders priced in another currency (before a settings change) are left out.
+    let currency = default_currency(&conn).map_err(|e| e.to_string())?;
+    let (revenue_7d, revenue_30d, avg_order_value_30d): (i64, i64, Option<f64>) = conn.query_row(
+        "SELECT COALESCE(SUM(CASE WHEN datetime(completed_at) >= datetime('now','-7 days') THEN amount_to_collect END), 0),
+                COALESCE(SUM(amount_to_collect), 0),
+                ROUND(AVG(amount_to_collect), 2)
+         FROM active_orders
+         WHERE status = 'delivered' AND currency = ?1
+           AND datetime(completed_at) >= datetime('now','-30 days')",
+        params![currency], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+    ).map_err(|e| e.to_string())?;
     let unique_clients: i64 = conn.query_row(
-        "SELECT COUNT(DISTINCT phone) FROM orders",
+        "SELECT COUNT(DISTINCT customer_id) FROM active_orders",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
     let returning_clients_pct: f64 = conn.query_row(
-        "WITH per_client AS (SELECT phone, COUNT(*) AS cnt FROM orders GROUP BY phone)
+        "WITH per_client AS (
+           SELECT customer_id, COUNT(*) AS cnt FROM active_orders WHERE customer_id IS NOT NULL GROUP BY customer_id
+         )
          SELECT COALESCE(ROUND(100.0 * SUM(CASE WHEN cnt > 1 THEN 1 ELSE 0 END) / NULLIF(COUNT(*),0), 1), 0.0)
          FROM per_client",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
 
     let avg_lead_days: Option<f64> = conn.query_row(
-        "SELECT ROUND(AVG(julianday(date(delivery_date)) - julianday(datetime(created_at))), 2) FROM orders",
+        "SELECT ROUND(AVG(julianday(date(delivery_date)) - julianday(datetime(created_at))), 2) FROM active_orders",
         [], |r| r.get::<_, Option<f64>>(0)
     ).map_err(|e| e.to_string())?;
 
     let median_lead_days: Option<f64> = conn.query_row(
         "WITH lt AS (
            SELECT (julianday(date(delivery_date)) - julianday(datetime(created_at))) AS d
-           FROM orders
+           FROM active_orders
            WHERE delivery_date IS NOT NULL
            ORDER BY d
          )
@@ -60,16 +85,48 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         [], |r| r.get::<_, Option<f64>>(0)
     ).map_err(|e| e.to_string())?;
 
+    // Actual lead time & punctuality: created -> first "delivered" event (last 90 days)
+    let on_time_rate_pct: Option<f64> = conn.query_row(
+        &format!("WITH {delivered}
+         SELECT ROUND(100.0 * SUM(CASE WHEN date(d.delivered_at, 'localtime') <= date(o.delivery_date) THEN 1 ELSE 0 END)
+                      / NULLIF(COUNT(*), 0), 1)
+         FROM delivered d
+         JOIN active_orders o ON o.id = d.order_id
+         WHERE datetime(d.delivered_at) >= datetime('now','-90 days')"),
+        [], |r| r.get::<_, Option<f64>>(0)
+    ).map_err(|e| e.to_string())?;
+
+    let avg_actual_lead_days: Option<f64> = conn.query_row(
+        &format!("WITH {delivered}
+         SELECT ROUND(AVG(julianday(datetime(d.delivered_at)) - julianday(datetime(o.created_at))), 2)
+         FROM delivered d
+         JOIN active_orders o ON o.id = d.order_id"),
+        [], |r| r.get::<_, Option<f64>>(0)
+    ).map_err(|e| e.to_string())?;
+
+    let median_actual_lead_days: Option<f64> = conn.query_row(
+        &format!("WITH {delivered},
+         lt AS (
+           SELECT (julianday(datetime(d.delivered_at)) - julianday(datetime(o.created_at))) AS d
+           FROM delivered d
+           JOIN active_orders o ON o.id = d.order_id
+           ORDER BY d
+         )
+         SELECT ROUND(d, 2) FROM lt
+         LIMIT 1 OFFSET (SELECT COUNT(*) FROM lt) / 2"),
+        [], |r| r.get::<_, Option<f64>>(0)
+    ).optional().map_err(|e| e.to_string())?.flatten();
+
     // Top delivery company (90d) + share
     let mut top_delivery_company: Option<TopItemShare> = None;
     let total_90d: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM orders WHERE date(created_at) >= date('now','-90 days')",
+        "SELECT COUNT(*) FROM active_orders WHERE date(created_at) >= date('now','-90 days')",
         [], |r| r.get(0)
     ).map_err(|e| e.to_string())?;
     {
         let mut st = conn.prepare(
             "SELECT COALESCE(NULLIF(TRIM(delivery_company),''),'(Unknown)') AS name, COUNT(*) AS c
-             FROM orders
+             FROM active_orders
              WHERE date(created_at) >= date('now','-90 days')
              GROUP BY name
              ORDER BY c DESC
@@ -85,10 +142,11 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
     // Top article & city (90d)
     let top_article: Option<NameCount> = {
         let mut st = conn.prepare(
-            "SELECT article_name, COUNT(*) AS c
-             FROM orders
-             WHERE date(created_at) >= date('now','-90 days')
-             GROUP BY article_name
+            "SELECT i.article_name, SUM(i.quantity) AS c
+             FROM order_items i
+             JOIN active_orders o ON o.id = i.order_id
+             WHERE date(o.created_at) >= date('now','-90 days')
+             GROUP BY i.article_name
              ORDER BY c DESC
              LIMIT 1"
         ).map_err(|e| e.to_string())?;
@@ -98,7 +156,7 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
     let top_city: Option<NameCount> = {
         let mut st = conn.prepare(
             "SELECT city, COUNT(*) AS c
-             FROM orders
+             FROM active_orders
              WHERE date(created_at) >= date('now','-90 days')
              GROUP BY city
              ORDER BY c DESC
@@ -107,6 +165,7 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         st.query_row([], |r| Ok(NameCount { name: r.get(0)?, count: r.get(1)? }))
           .optional().map_err(|e| e.to_string())?
     };
+    let low_stock_articles = count_low_stock(&conn).map_err(|e| e.to_string())?;
 
     let kpis = Kpis {
         total_orders,
@@ -120,16 +179,38 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         returning_clients_pct,
         avg_lead_days,
         median_lead_days,
+        avg_actual_lead_days,
+        median_actual_lead_days,
+        on_time_rate_pct,
         top_delivery_company,
         top_article,
         top_city,
+        low_stock_articles,
+        currency: currency.clone(),
+        revenue_7d,
+        revenue_30d,
+        avg_order_value_30d,
+    };
+
+    // ---- Orders per status ----
+    let status_breakdown: Vec<NameCount> = {
+        let mut st = conn.prepare(
+            "SELECT status AS name, COUNT(*) AS cnt
+             FROM active_orders GROUP BY status
+             ORDER BY CASE status
+               WHEN 'pending' THEN 1 WHEN 'confirmed' THEN 2 WHEN 'with_courier' THEN 3
+               WHEN 'delivered' THEN 4 WHEN 'returned' THEN 5 ELSE 6 END"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map([], |r| Ok(NameCount { name: r.get(0)?, count: r.get(1)? }))
+            .map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
     };
 
     // ---- Orders over time (weekly) ----
     let orders_over_time_weekly: Vec<TimeCount> = {
         let mut st = conn.prepare(
             "SELECT strftime('%Y-%W', datetime(created_at)) AS period, COUNT(*) AS cnt
-             FROM orders GROUP BY period ORDER BY period"
+             FROM active_orders GROUP BY period ORDER BY period"
         ).map_err(|e| e.to_string())?;
         let rows = st.query_map([], |r| Ok(TimeCount { period: r.get(0)?, count: r.get(1)? }))
             .map_err(|e| e.to_string())?;
@@ -139,7 +220,7 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
     let orders_over_time_weekly_by_done: Vec<TimeDoneCount> = {
         let mut st = conn.prepare(
             "SELECT strftime('%Y-%W', datetime(created_at)) AS period, done, COUNT(*) AS cnt
-             FROM orders GROUP BY period, done ORDER BY period, done"
+             FROM active_orders GROUP BY period, done ORDER BY period, done"
         ).map_err(|e| e.to_string())?;
         let rows = st.query_map([], |r| Ok(TimeDoneCount { period: r.get(0)?, done: r.get(1)?, count: r.get(2)? }))
             .map_err(|e| e.to_string())?;
@@ -152,9 +233,9 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
             "SELECT strftime('%Y-%W', date(delivery_date)) AS week,
                     COALESCE(NULLIF(TRIM(delivery_company),''),'(Unknown)') AS company,
                     COUNT(*) AS cnt
-             FROM orders
+             FROM active_orders
              WHERE date(delivery_date) BETWEEN date('now','localtime') AND date('now','localtime','+84 days')
-               AND done = 0
+               AND status IN ('pending','confirmed','with_courier')
              GROUP BY week, company
              ORDER BY week, company"
         ).map_err(|e| e.to_string())?;
@@ -168,8 +249,8 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut st = conn.prepare(
             "SELECT ROUND(julianday(date(delivery_date)) - julianday(datetime(created_at))) AS lead_days,
                     COUNT(*) AS cnt
-             FROM orders
-             WHERE delivery_date IS NOT NULL
+             FROM active_orders
+             WHERE date(delivery_date) IS NOT NULL
              GROUP BY lead_days
              ORDER BY lead_days"
         ).map_err(|e| e.to_string())?;
@@ -178,11 +259,13 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
     };
 
-    // ---- Top articles (top 10) ----
+    // ---- Top articles (top 10, by units across order items) ----
     let top_articles: Vec<NameCount> = {
         let mut st = conn.prepare(
-            "SELECT article_name AS name, COUNT(*) AS cnt
-             FROM orders GROUP BY article_name ORDER BY cnt DESC LIMIT 10"
+            "SELECT i.article_name AS name, SUM(i.quantity) AS cnt
+             FROM order_items i
+             JOIN active_orders o ON o.id = i.order_id
+             GROUP BY i.article_name ORDER BY cnt DESC LIMIT 10"
         ).map_err(|e| e.to_string())?;
         let rows = st.query_map([], |r| Ok(NameCount { name: r.get(0)?, count: r.get(1)? }))
             .map_err(|e| e.to_string())?;
@@ -193,7 +276,7 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
     let company_share_90d: Vec<NameCount> = {
         let mut st = conn.prepare(
             "SELECT COALESCE(NULLIF(TRIM(delivery_company),''),'(Unknown)') AS name, COUNT(*) AS cnt
-             FROM orders
+             FROM active_orders
              WHERE date(created_at) >= date('now','-90 days')
              GROUP BY name
              ORDER BY cnt DESC"
@@ -203,19 +286,100 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
     };
 
+    // ---- Revenue per company (last 90 days) ----
+    let revenue_by_company_90d: Vec<NameAmount> = {
+        let mut st = conn.prepare(
+            "SELECT COALESCE(NULLIF(TRIM(delivery_company),''),'(Unknown)') AS name,
+                    SUM(amount_to_collect) AS amount, COUNT(*) AS cnt
+             FROM active_orders
+             WHERE status = 'delivered' AND currency = ?1
+               AND datetime(completed_at) >= datetime('now','-90 days')
+             GROUP BY name
+             ORDER BY amount DESC"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map(params![currency], |r| Ok(NameAmount { name: r.get(0)?, amount: r.get(1)?, orders: r.get(2)? }))
+            .map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+
+    // ---- Cash still held by couriers ----
+    let unsettled_cash_by_company: Vec<NameAmount> = {
+        let mut st = conn.prepare(
+            "SELECT COALESCE(NULLIF(TRIM(o.delivery_company),''),'(Unknown)') AS name,
+                    SUM(o.amount_to_collect) AS amount, COUNT(*) AS cnt
+             FROM active_orders o
+             WHERE o.status = 'delivered' AND o.currency = ?1
+               AND NOT EXISTS (SELECT 1 FROM settlement_orders so WHERE so.order_id = o.id)
+             GROUP BY name
+             ORDER BY amount DESC"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map(params![currency], |r| Ok(NameAmount { name: r.get(0)?, amount: r.get(1)?, orders: r.get(2)? }))
+            .map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+
+    // ---- Return rates (orders shipped in the last 90 days) ----
+    // A returned order without a return record (refused at the door) counts as fully returned.
+    let return_rate_by_article_90d: Vec<ReturnRate> = {
+        let mut st = conn.prepare(
+            "WITH shipped AS (
+               SELECT o.id, o.status, EXISTS(SELECT 1 FROM returns r WHERE r.order_id = o.id) AS has_return
+               FROM active_orders o
+               WHERE o.status IN ('with_courier','delivered','returned')
+                 AND date(o.created_at) >= date('now','-90 days')
+             )
+             SELECT i.article_name, SUM(i.quantity) AS units,
+                    SUM(CASE WHEN s.has_return THEN COALESCE((SELECT SUM(ri.quantity) FROM return_items ri WHERE ri.order_item_id = i.id), 0)
+                             WHEN s.status = 'returned' THEN i.quantity
+                             ELSE 0 END) AS returned
+             FROM order_items i
+             JOIN shipped s ON s.id = i.order_id
+             GROUP BY i.article_name
+             HAVING returned > 0
+             ORDER BY returned DESC, units DESC
+             LIMIT 10"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map([], |r| {
+            let shipped: i64 = r.get(1)?;
+            let returned: i64 = r.get(2)?;
+            Ok(ReturnRate { name: r.get(0)?, shipped, returned, rate_pct: (1000.0 * returned as f64 / shipped as f64).round() / 10.0 })
+        }).map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+    let return_rate_by_company_90d: Vec<ReturnRate> = {
+        let mut st = conn.prepare(
+            "SELECT COALESCE(NULLIF(TRIM(o.delivery_company),''),'(Unknown)') AS name,
+                    COUNT(*) AS shipped,
+                    SUM(o.status = 'returned'
+                        OR EXISTS(SELECT 1 FROM returns r WHERE r.order_id = o.id AND r.kind = 'return')) AS returned
+             FROM active_orders o
+             WHERE o.status IN ('with_courier','delivered','returned')
+               AND date(o.created_at) >= date('now','-90 days')
+             GROUP BY name
+             ORDER BY shipped DESC"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map([], |r| {
+            let shipped: i64 = r.get(1)?;
+            let returned: i64 = r.get(2)?;
+            Ok(ReturnRate { name: r.get(0)?, shipped, returned, rate_pct: (1000.0 * returned as f64 / shipped as f64).round() / 10.0 })
+        }).map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+
     // ---- New vs Returning (monthly) ----
     let new_vs_returning_monthly: Vec<(String, i64, i64)> = {
         let mut st = conn.prepare(
             "WITH first_seen AS (
-               SELECT phone, MIN(date(created_at)) AS first_date FROM orders GROUP BY phone
+               SELECT customer_id, MIN(date(created_at)) AS first_date FROM active_orders GROUP BY customer_id
              ),
              orders_m AS (
-               SELECT phone, strftime('%Y-%m', date(created_at)) AS ym, date(created_at) AS d
-               FROM orders
+               SELECT customer_id, strftime('%Y-%m', date(created_at)) AS ym, date(created_at) AS d
+               FROM active_orders
+               WHERE customer_id IS NOT NULL
              )
              SELECT ym,
-                    SUM(CASE WHEN d = (SELECT first_date FROM first_seen f WHERE f.phone = o.phone) THEN 1 ELSE 0 END) AS new_clients,
-                    SUM(CASE WHEN d >  (SELECT first_date FROM first_seen f WHERE f.phone = o.phone) THEN 1 ELSE 0 END) AS returning_clients
+                    SUM(CASE WHEN d = (SELECT first_date FROM first_seen f WHERE f.customer_id = o.customer_id) THEN 1 ELSE 0 END) AS new_clients,
+                    SUM(CASE WHEN d >  (SELECT first_date FROM first_seen f WHERE f.customer_id = o.customer_id) THEN 1 ELSE 0 END) AS returning_clients
              FROM orders_m o
              GROUP BY ym
              ORDER BY ym"
@@ -230,7 +394,7 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut st = conn.prepare(
             "WITH ages AS (
                SELECT CAST(julianday('now') - julianday(datetime(created_at)) AS INT) AS age_days
-               FROM orders WHERE done = 0
+               FROM active_orders WHERE status IN ('pending','confirmed','with_courier')
              )
              SELECT
                CASE
@@ -253,13 +417,43 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
     };
 
+    // ---- Backlog aging per open status ----
+    let backlog_age_buckets_by_status: Vec<StatusBucketCount> = {
+        let mut st = conn.prepare(
+            "WITH ages AS (
+               SELECT status, CAST(julianday('now') - julianday(datetime(created_at)) AS INT) AS age_days
+               FROM active_orders WHERE status IN ('pending','confirmed','with_courier')
+             )
+             SELECT
+               status,
+               CASE
+                 WHEN age_days < 3  THEN '0-2'
+                 WHEN age_days < 7  THEN '3-6'
+                 WHEN age_days < 14 THEN '7-13'
+                 WHEN age_days < 30 THEN '14-29'
+                 ELSE '30+'
+               END AS bucket,
+               COUNT(*) AS cnt
+             FROM ages
+             GROUP BY status, bucket
+             ORDER BY
+               CASE status WHEN 'pending' THEN 1 WHEN 'confirmed' THEN 2 ELSE 3 END,
+               CASE bucket
+                 WHEN '0-2' THEN 1 WHEN '3-6' THEN 2 WHEN '7-13' THEN 3
+                 WHEN '14-29' THEN 4 ELSE 5 END"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map([], |r| Ok(StatusBucketCount { status: r.get(0)?, bucket: r.get(1)?, count: r.get(2)? }))
+            .map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+
     // ---- Activity heatmap ----
     let activity_heatmap: Vec<HeatCell> = {
         let mut st = conn.prepare(
             "SELECT CAST(strftime('%w', datetime(created_at)) AS INT) AS weekday,
                     CAST(strftime('%H', datetime(created_at)) AS INT) AS hour,
                     COUNT(*) AS cnt
-             FROM orders
+             FROM active_orders
              GROUP BY weekday, hour
              ORDER BY weekday, hour"
         ).map_err(|e| e.to_string())?;
@@ -273,8 +467,8 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut st = conn.prepare(
             "SELECT id, article_name, client_name, city, delivery_company, delivery_date,
                     CAST(julianday('now') - julianday(datetime(created_at)) AS INT) AS age_days
-             FROM orders
-             WHERE done = 0 AND date(delivery_date) < date('now','localtime')
+             FROM active_orders
+             WHERE status IN ('pending','confirmed','with_courier') AND date(delivery_date) < date('now','localtime')
              ORDER BY date(delivery_date) ASC
              LIMIT 10"
         ).map_err(|e| e.to_string())?;
@@ -285,6 +479,38 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
     };
 
+    let exceptions_failed_attempts: Vec<FailedAttemptsRow> = {
+        let mut st = conn.prepare(
+            "WITH failed AS (
+               SELECT order_id, COUNT(*) AS n, MAX(attempted_at) AS last_at
+               FROM delivery_attempts
+               WHERE outcome = 'failed'
+               GROUP BY order_id
+               HAVING n >= 2
+             )
+             SELECT o.id, o.article_name, o.client_name, o.city, o.delivery_company, o.tracking_number,
+                    f.n, f.last_at,
+                    (SELECT a.reason FROM delivery_attempts a
+                      WHERE a.order_id = o.id AND a.outcome = 'failed'
+                      ORDER BY a.attempted_at DESC, a.id DESC LIMIT 1)
+             FROM failed f
+             JOIN active_orders o ON o.id = f.order_id
+             WHERE o.status = 'with_courier'
+             ORDER BY f.n DESC, f.last_at ASC
+             LIMIT 10"
+        ).map_err(|e| e.to_string())?;
+        let rows = st.query_map([], |r| Ok(FailedAttemptsRow {
+            id: r.get(0)?, article_name: r.get(1)?, client_name: r.get(2)?, city: r.get(3)?,
+            delivery_company: r.get(4)?, tracking_number: r.get(5)?, failed_attempts: r.get(6)?,
+            last_attempt_at: r.get(7)?, last_reason: r.get(8)?,
+        })).map_err(|e| e.to_string())?;
+        let mut out = Vec::new(); for r in rows { out.push(r.map_err(|e| e.to_string())?); } out
+    };
+
+    // ---- Stock shortages ----
+    let mut low_stock = load_stock_levels(&conn, true).map_err(|e| e.to_string())?;
+    low_stock.truncate(10);
+
     let data = DashboardData {
         kpis,
         orders_over_time_weekly,
@@ -293,10 +519,20 @@ pub fn get_dashboard_data(state: tauri::State<AppState>) -> Result<DashboardData
         lead_time_histogram,
         top_articles,
         company_share_90d,
+        revenue_by_company_90d,
+        unsettled_cash_by_company,
+        return_rate_by_article_90d,
+        return_rate_by_company_90d,
         new_vs_returning_monthly,
         backlog_age_buckets,
+        backlog_age_buckets_by_status,
+        status_breakdown,
         activity_heatmap,
-        exceptions: Exceptions { overdue_top10: exceptions_overdue_top10 },
+        low_stock,
+        exceptions: Exceptions {
+            overdue_top10: exceptions_overdue_top10,
+            repeated_failed_attempts: exceptions_failed_attempts,
+        },
     };
 
     Ok(data)
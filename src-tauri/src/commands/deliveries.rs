@@ -0,0 +1,174 @@
+// src/commands/deliveries.rs
+// What happens once the parcel is with the courier: waybill number and the
+// courier's reports of each delivery attempt.
+use crate::app_state::AppState;
+use crate::commands::journal::{capture_order_state, record_change};
+use crate::commands::orders::{apply_status, current_status};
+use crate::db::{ensure_schema, max_failed_attempts, open_db, record_order_event};
+use crate::models::delivery::{DeliveryAttempt, DeliveryAttemptInput};
+use crate::models::orders::{OrderStatus, StatusError};
+use rusqlite::{params, OptionalExtension, TransactionBehavior};
+use serde_json::json;
+
+#[tauri::command]
+pub fn set_order_tracking_number(
+    state: tauri::State<AppState>,
+    id: i64,
+    tracking_number: Option<String>,
+) -> Result<(), String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tracking = tracking_number.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    let before: Option<String> = tx
+        .query_row(r#"SELECT tracking_number FROM active_orders WHERE id = ?1"#, [id], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Order {id} not found"))?;
+    if before == tracking {
+        return Ok(());
+    }
+
+    tx.execute(
+        r#"UPDATE orders SET tracking_number = ?1 WHERE id = ?2"#,
+        params![tracking, id],
+    )
+    .map_err(|e| e.to_string())?;
+    record_order_event(
+        &tx,
+        id,
+        "edited",
+        Some(&json!({ "trackingNumber": before }).to_string()),
+        Some(&json!({ "trackingNumber": tracking }).to_string()),
+    )
+    .map_err(|e| e.to_string())?;
+
+    record_change(&tx, id, "set_order_tracking_number", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Record a courier's delivery attempt and return the order's resulting status.
+/// A successful attempt delivers the order; once the failed attempts reach the
+/// `maxFailedAttempts` setting the order goes back as returned.
+#[tauri::command]
+pub fn log_delivery_attempt(
+    state: tauri::State<AppState>,
+    order_id: i64,
+    attempt: DeliveryAttemptInput,
+) -> Result<OrderStatus, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let outcome = attempt.outcome.trim().to_lowercase();
+    if !matches!(outcome.as_str(), "delivered" | "failed" | "rescheduled") {
+        return Err(format!(
+            "Unknown attempt outcome '{}': expected delivered, failed or rescheduled",
+            attempt.outcome
+        ));
+    }
+    let non_empty = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let attempted_at = non_empty(attempt.attempted_at);
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let status = current_status(&tx, order_id)?;
+    if status != OrderStatus::WithCourier {
+        return Err(format!(
+            "Delivery attempts can only be logged while the order is with the courier (it is {})",
+            status.as_str()
+        ));
+    }
+    if let Some(at) = &attempted_at {
+        let valid: bool = tx
+            .query_row(r#"SELECT datetime(?1) IS NOT NULL"#, [at], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        if !valid {
+            return Err(format!("Invalid attempt time '{at}'"));
+        }
+    }
+
+    tx.execute(
+        r#"
+        INSERT INTO delivery_attempts (order_id, attempted_at, outcome, reason, note)
+        VALUES (?1, COALESCE(?2, strftime('%Y-%m-%dT%H:%M:%fZ','now')), ?3, ?4, ?5)
+        "#,
+        params![order_id, attempted_at, outcome, non_empty(attempt.reason), non_empty(attempt.note)],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let next = match outcome.as_str() {
+        "delivered" => Some(OrderStatus::Delivered),
+        "failed" => {
+            let limit = max_failed_attempts(&tx).map_err(|e| e.to_string())?;
+            let failed: i64 = tx
+                .query_row(
+                    r#"SELECT COUNT(*) FROM delivery_attempts WHERE order_id = ?1 AND outcome = 'failed'"#,
+                    [order_id],
+                    |r| r.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            (limit > 0 && failed >= limit).then_some(OrderStatus::Returned)
+        }
+        _ => None,
+    };
+
+    let result = match next {
+        Some(to) => {
+            if !status.can_transition_to(to) {
+                return Err(StatusError::IllegalTransition { from: status, to }.to_string());
+            }
+            apply_status(&tx, order_id, status, to).map_err(|e| e.to_string())?;
+            to
+        }
+        None => status,
+    };
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn list_delivery_attempts(
+    state: tauri::State<AppState>,
+    order_id: i64,
+) -> Result<Vec<DeliveryAttempt>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, order_id, attempted_at, outcome, reason, note
+            FROM delivery_attempts
+            WHERE order_id = ?1
+            ORDER BY attempted_at ASC, id ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([order_id], |row| {
+            Ok(DeliveryAttempt {
+                id: row.get(0)?,
+                order_id: row.get(1)?,
+                attempted_at: row.get(2)?,
+                outcome: row.get(3)?,
+                reason: row.get(4)?,
+                note: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
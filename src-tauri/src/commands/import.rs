@@ -0,0 +1,361 @@
+// src/commands/import.rs
+use crate::app_state::AppState;
+use crate::commands::orders::{insert_order, load_order_items};
+use crate::csv::{decode_text, detect_delimiter, parse_records, CsvRecord};
+use crate::db::{default_country_code, ensure_schema, open_db};
+use crate::models::import::{
+    CsvColumnMapping, CsvDateFormat, CsvImportOptions, CsvImportReport, CsvPreview, CsvRowReport,
+};
+use crate::models::orders::{NewOrderInput, OrderItemInput};
+use crate::money::parse_amount;
+use crate::phone::normalize_phone;
+use crate::util::is_valid_ymd;
+use rusqlite::{Connection, TransactionBehavior};
+use std::collections::HashMap;
+
+const PREVIEW_RECORDS: usize = 6;
+
+fn read_records(content: &[u8], encoding: Option<&str>, delimiter: Option<&str>) -> Result<(char, Vec<CsvRecord>), String> {
+    let text = decode_text(content, encoding)?;
+    let delimiter = match delimiter.filter(|d| !d.is_empty()) {
+        None => detect_delimiter(&text),
+        Some(d) => {
+            let mut chars = d.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != '"' && c != '\n' && c != '\r' => c,
+                _ => return Err(format!("Delimiter must be a single character, got '{d}'")),
+            }
+        }
+    };
+    Ok((delimiter, parse_records(&text, delimiter)?))
+}
+
+// A date cell as yyyy-mm-dd; a time after the date ("2024-05-01 10:30") is ignored
+fn parse_date(cell: &str, format: CsvDateFormat) -> Option<String> {
+    let date = cell.split([' ', 'T']).next().unwrap_or_default();
+    let parts: Vec<&str> = date.split(['-', '/', '.']).collect();
+    let [a, b, c] = parts[..] else { return None };
+    let (y, m, d) = match format {
+        CsvDateFormat::Ymd => (a, b, c),
+        CsvDateFormat::Dmy => (c, b, a),
+        CsvDateFormat::Mdy => (c, a, b),
+    };
+    let year = match y.len() {
+        4 => y.to_string(),
+        2 => format!("20{y}"),
+        _ => return None,
+    };
+    if !(1..=2).contains(&m.len()) || !(1..=2).contains(&d.len()) {
+        return None;
+    }
+    let out = format!("{year}-{m:0>2}-{d:0>2}");
+    is_valid_ymd(&out).then_some(out)
+}
+
+// Column index of each mapped field
+struct Columns {
+    client_name: usize,
+    phone: usize,
+    article_name: usize,
+    city: Option<usize>,
+    address: Option<usize>,
+    delivery_company: Option<usize>,
+    delivery_date: Option<usize>,
+    description: Option<usize>,
+    quantity: Option<usize>,
+    unit_price: Option<usize>,
+    delivery_fee: Option<usize>,
+    discount: Option<usize>,
+}
+
+// A mapping entry is a header name (any case) or a 1-based column number
+fn resolve_columns(mapping: &CsvColumnMapping, headers: &[String], width: usize) -> Result<Columns, String> {
+    let find = |spec: &Option<String>, label: &str| -> Result<Option<usize>, String> {
+        let Some(spec) = spec.as_deref().map(str::trim).filter(|s| !s.is_empty()) else { return Ok(None) };
+        let index = headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(spec))
+            .or_else(|| spec.parse::<usize>().ok().filter(|n| *n >= 1).map(|n| n - 1));
+        match index {
+            Some(i) if i < width => Ok(Some(i)),
+            _ => Err(format!("Column '{spec}' for the {label} is not in the file")),
+        }
+    };
+    let required = |spec: &Option<String>, label: &str| -> Result<usize, String> {
+        find(spec, label)?.ok_or_else(|| format!("Map a column to the {label}"))
+    };
+    Ok(Columns {
+        client_name: required(&mapping.client_name, "client name")?,
+        phone: required(&mapping.phone, "phone")?,
+        article_name: required(&mapping.article_name, "article")?,
+        city: find(&mapping.city, "city")?,
+        address: find(&mapping.address, "address")?,
+        delivery_company: find(&mapping.delivery_company, "delivery company")?,
+        delivery_date: find(&mapping.delivery_date, "delivery date")?,
+        description: find(&mapping.description, "description")?,
+        quantity: find(&mapping.quantity, "quantity")?,
+        unit_price: find(&mapping.unit_price, "unit price")?,
+        delivery_fee: find(&mapping.delivery_fee, "delivery fee")?,
+        discount: find(&mapping.discount, "discount")?,
+    })
+}
+
+// Order for one record, or every problem found in it
+fn row_to_order(
+    record: &CsvRecord,
+    cols: &Columns,
+    options: &CsvImportOptions,
+    country_code: &str,
+) -> Result<NewOrderInput, Vec<String>> {
+    let cell = |col: Option<usize>| col.and_then(|i| record.fields.get(i)).map_or("", |s| s.trim());
+    let mut errors = Vec::new();
+
+    let client_name = cell(Some(cols.client_name));
+    if client_name.is_empty() {
+        errors.push("Client name is required".to_string());
+    }
+    let phone = cell(Some(cols.phone));
+    if let Err(e) = normalize_phone(phone, country_code) {
+        errors.push(e.to_string());
+    }
+    let article_name = cell(Some(cols.article_name));
+    if article_name.is_empty() {
+        errors.push("Article is required".to_string());
+    }
+    let delivery_company = match cell(cols.delivery_company) {
+        "" => options.default_delivery_company.as_deref().unwrap_or_default().trim(),
+        company => company,
+    };
+    if delivery_company.is_empty() {
+        errors.push("Delivery company is required".to_string());
+    }
+    // Left empty, the company's delivery terms fill it in
+    let delivery_date = match cell(cols.delivery_date) {
+        "" => String::new(),
+        raw => parse_date(raw, options.date_format).unwrap_or_else(|| {
+            errors.push(format!("Invalid delivery date '{raw}'"));
+            String::new()
+        }),
+    };
+    let quantity = match cell(cols.quantity) {
+        "" => 1,
+        raw => match raw.parse::<i64>() {
+            Ok(q) if q >= 1 => q,
+            _ => {
+                errors.push(format!("Invalid quantity '{raw}'"));
+                1
+            }
+        },
+    };
+    let mut amount = |col: Option<usize>, label: &str| match cell(col) {
+        "" => None,
+        raw => parse_amount(raw).map_err(|e| errors.push(format!("{label}: {e}"))).ok(),
+    };
+    let unit_price = amount(cols.unit_price, "Unit price");
+    let delivery_fee = amount(cols.delivery_fee, "Delivery fee");
+    let discount = amount(cols.discount, "Discount");
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(NewOrderInput {
+        client_name: client_name.to_string(),
+        article_name: String::new(),
+        items: vec![OrderItemInput {
+            article_id: None,
+            article_name: article_name.to_string(),
+            quantity,
+            unit_price,
+            note: None,
+        }],
+        phone: phone.to_string(),
+        city: cell(cols.city).to_string(),
+        address: cell(cols.address).to_string(),
+        delivery_company: delivery_company.to_string(),
+        delivery_date,
+        description: Some(cell(cols.description).to_string()).filter(|d| !d.is_empty()),
+        delivery_fee,
+        discount,
+    })
+}
+
+// Articles of an order as a comparable set: names in any case, quantities summed per name
+fn item_set(conn: &Connection, order_id: i64) -> Result<Vec<(String, i64)>, rusqlite::Error> {
+    let mut set: Vec<(String, i64)> = Vec::new();
+    for it in load_order_items(conn, order_id)? {
+        let name = it.article_name.trim().to_lowercase();
+        match set.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, quantity)) => *quantity += it.quantity,
+            None => set.push((name, it.quantity)),
+        }
+    }
+    set.sort();
+    Ok(set)
+}
+
+// Another order for the same client and delivery date with the same articles and quantities
+fn find_duplicate(conn: &Connection, id: i64) -> Result<Option<(i64, String)>, rusqlite::Error> {
+    let candidates: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT o.id, COALESCE(o.reference, '')
+            FROM active_orders n
+            JOIN active_orders o
+              ON o.customer_id = n.customer_id AND o.id <> n.id AND o.delivery_date = n.delivery_date
+            WHERE n.id = ?1
+            ORDER BY o.id
+            "#,
+        )?;
+        let rows = stmt.query_map([id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        let mut tmp = Vec::new();
+        for r in rows { tmp.push(r?); }
+        tmp
+    };
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    let items = item_set(conn, id)?;
+    for (other, reference) in candidates {
+        if item_set(conn, other)? == items {
+            return Ok(Some((other, reference)));
+        }
+    }
+    Ok(None)
+}
+
+/// First records of a CSV file as split with the given (or detected) delimiter.
+#[tauri::command]
+pub fn preview_csv(
+    content: Vec<u8>,
+    encoding: Option<String>,
+    delimiter: Option<String>,
+) -> Result<CsvPreview, String> {
+    let (delimiter, records) = read_records(&content, encoding.as_deref(), delimiter.as_deref())?;
+    Ok(CsvPreview {
+        delimiter: delimiter.to_string(),
+        records: records.into_iter().take(PREVIEW_RECORDS).map(|r| r.fields).collect(),
+    })
+}
+
+/// Import orders from CSV bytes. Every row goes through the same validation and
+/// insert as `save_order`, inside one transaction. With `dry_run` the transaction
+/// is rolled back, so the report shows exactly what a real import would do.
+/// Unless `skipInvalid` is set, a single invalid row imports nothing.
+/// Imported orders are not added to the undo journal.
+#[tauri::command]
+pub fn import_orders_csv(
+    state: tauri::State<AppState>,
+    content: Vec<u8>,
+    options: CsvImportOptions,
+    dry_run: bool,
+) -> Result<CsvImportReport, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (_, mut records) = read_records(&content, options.encoding.as_deref(), options.delimiter.as_deref())?;
+    let headers = if options.has_header && !records.is_empty() { records.remove(0).fields } else { Vec::new() };
+    let width = records.iter().map(|r| r.fields.len()).chain([headers.len()]).max().unwrap_or(0);
+    let cols = resolve_columns(&options.mapping, &headers, width)?;
+    let cc = default_country_code(&conn).map_err(|e| e.to_string())?;
+
+    let mut tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let mut report = CsvImportReport {
+        dry_run,
+        total_rows: records.len(),
+        valid_rows: 0,
+        imported: 0,
+        rows: Vec::new(),
+    };
+    let mut lines_by_id: HashMap<i64, usize> = HashMap::new();
+    for record in &records {
+        let mut row = CsvRowReport { line: record.line, errors: Vec::new(), warnings: Vec::new() };
+        match row_to_order(record, &cols, &options, &cc) {
+            Err(errors) => row.errors = errors,
+            Ok(order) => {
+                // A failed insert rolls back to here without touching the other rows
+                let sp = tx.savepoint().map_err(|e| e.to_string())?;
+                match insert_order(&sp, &order) {
+                    Err(e) => row.errors.push(e),
+                    Ok(id) => {
+                        if let Some((other, reference)) = find_duplicate(&sp, id).map_err(|e| e.to_string())? {
+                            row.warnings.push(match lines_by_id.get(&other) {
+                                Some(line) => format!("Same client, articles and delivery date as line {line}"),
+                                None => format!("Same client, articles and delivery date as order {reference}"),
+                            });
+                        }
+                        sp.commit().map_err(|e| e.to_string())?;
+                        lines_by_id.insert(id, record.line);
+                        report.valid_rows += 1;
+                    }
+                }
+            }
+        }
+        if !row.errors.is_empty() || !row.warnings.is_empty() {
+            report.rows.push(row);
+        }
+    }
+
+    let has_errors = report.valid_rows < report.total_rows;
+    if dry_run || (has_errors && !options.skip_invalid) {
+        tx.rollback().map_err(|e| e.to_string())?;
+    } else {
+        tx.commit().map_err(|e| e.to_string())?;
+        report.imported = report.valid_rows;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_follow_the_chosen_order() {
+        assert_eq!(parse_date("2026-10-20", CsvDateFormat::Ymd).as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_date("20/10/2026", CsvDateFormat::Dmy).as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_date("10/20/2026", CsvDateFormat::Mdy).as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_date("1.2.26", CsvDateFormat::Dmy).as_deref(), Some("2026-02-01"));
+    }
+
+    #[test]
+    fn a_time_after_the_date_is_ignored() {
+        assert_eq!(parse_date("2026-10-20 10:30", CsvDateFormat::Ymd).as_deref(), Some("2026-10-20"));
+        assert_eq!(parse_date("2026-10-20T10:30:00Z", CsvDateFormat::Ymd).as_deref(), Some("2026-10-20"));
+    }
+
+    #[test]
+    fn impossible_or_malformed_dates_are_rejected() {
+        assert_eq!(parse_date("31/02/2026", CsvDateFormat::Dmy), None);
+        assert_eq!(parse_date("20/10/2026", CsvDateFormat::Mdy), None);
+        assert_eq!(parse_date("2026/10", CsvDateFormat::Ymd), None);
+        assert_eq!(parse_date("026-10-20", CsvDateFormat::Ymd), None);
+        assert_eq!(parse_date("020/10/2026", CsvDateFormat::Dmy), None);
+        assert_eq!(parse_date("", CsvDateFormat::Ymd), None);
+    }
+
+    #[test]
+    fn columns_resolve_by_header_or_number() {
+        let headers: Vec<String> = ["Nom", "Téléphone", "Article"].iter().map(|h| h.to_string()).collect();
+        let mapping = CsvColumnMapping {
+            client_name: Some(" nom ".into()),
+            phone: Some("2".into()),
+            article_name: Some("ARTICLE".into()),
+            city: Some("".into()),
+            ..Default::default()
+        };
+        let cols = resolve_columns(&mapping, &headers, 3).unwrap();
+        assert_eq!((cols.client_name, cols.phone, cols.article_name, cols.city), (0, 1, 2, None));
+
+        let unmapped = CsvColumnMapping { phone: None, ..mapping.clone() };
+        assert_eq!(resolve_columns(&unmapped, &headers, 3).err().unwrap(), "Map a column to the phone");
+        let out_of_range = CsvColumnMapping { phone: Some("4".into()), ..mapping };
+        assert_eq!(
+            resolve_columns(&out_of_range, &headers, 3).err().unwrap(),
+            "Column '4' for the phone is not in the file"
+        );
+    }
+}
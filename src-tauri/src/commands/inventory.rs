@@ -0,0 +1,332 @@
+// src/commands/inventory.rs
+// Stock is never stored as a number: levels are summed from `stock_movements`.
+//   on hand  = receipts + adjustments - shipments
+//   reserved = reservations - releases
+// Orders reserve their catalog items while pending/confirmed, release them on
+// cancel/delete and ship them when handed to the courier.
+use crate::app_state::AppState;
+use crate::db::{ensure_schema, open_db};
+use crate::models::inventory::{StockLevel, StockMovement, StockMovementInput};
+use crate::models::orders::OrderStatus;
+use rusqlite::{params, Connection, Row};
+
+const STOCK_LEVEL_SELECT: &str = r#"
+    SELECT a.id, a.name, a.sku,
+           COALESCE(m.on_hand, 0)  AS on_hand,
+           COALESCE(m.reserved, 0) AS reserved,
+           a.low_stock_threshold,
+           COALESCE(m.counted, 0)  AS counted
+    FROM articles a
+    LEFT JOIN (
+      SELECT article_id,
+             SUM(CASE kind WHEN 'receipt' THEN quantity
+                           WHEN 'adjustment' THEN quantity
+                           WHEN 'shipment' THEN -quantity
+                           ELSE 0 END) AS on_hand,
+             SUM(CASE kind WHEN 'reservation' THEN quantity
+                           WHEN 'release' THEN -quantity
+                           ELSE 0 END) AS reserved,
+             MAX(kind IN ('receipt','adjustment')) AS counted
+      FROM stock_movements
+      GROUP BY article_id
+    ) m ON m.article_id = a.id
+"#;
+
+// An article is short once it is oversold, or at/below its own threshold.
+// Oversold only counts for articles whose stock was ever entered, so a shop
+// that does not track some articles is not flooded with alerts.
+const LOW_STOCK_CONDITION: &str = r#"
+    ((COALESCE(m.counted, 0) = 1 AND COALESCE(m.on_hand, 0) - COALESCE(m.reserved, 0) < 0)
+     OR (a.low_stock_threshold IS NOT NULL
+         AND COALESCE(m.on_hand, 0) - COALESCE(m.reserved, 0) <= a.low_stock_threshold))
+"#;
+
+// Catalog quantities an order asks for (free-text items are not stocked)
+const ORDER_ARTICLES: &str = r#"
+    SELECT article_id, SUM(quantity)
+    FROM order_items
+    WHERE order_id = ?1 AND article_id IS NOT NULL
+    GROUP BY article_id
+"#;
+
+fn stock_level_from_row(row: &Row) -> rusqlite::Result<StockLevel> {
+    let on_hand: i64 = row.get(3)?;
+    let reserved: i64 = row.get(4)?;
+    let threshold: Option<i64> = row.get(5)?;
+    let counted: bool = row.get(6)?;
+    let available = on_hand - reserved;
+    Ok(StockLevel {
+        article_id: row.get(0)?,
+        article_name: row.get(1)?,
+        sku: row.get(2)?,
+        on_hand,
+        reserved,
+        available,
+        low_stock_threshold: threshold,
+        low: (counted && available < 0) || threshold.is_some_and(|t| available <= t),
+    })
+}
+
+/// Stock levels of active catalog articles, shortest first when `low_only`.
+pub(crate) fn load_stock_levels(
+    conn: &Connection,
+    low_only: bool,
+) -> Result<Vec<StockLevel>, rusqlite::Error> {
+    let filter = if low_only { LOW_STOCK_CONDITION } else { "1" };
+    let sql = format!(
+        "{STOCK_LEVEL_SELECT}
+         WHERE a.active = 1 AND {filter}
+         ORDER BY (COALESCE(m.on_hand, 0) - COALESCE(m.reserved, 0)) ASC, a.name ASC"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], stock_level_from_row)?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r?); }
+    Ok(out)
+}
+
+pub(crate) fn count_low_stock(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let sql = format!(
+        "SELECT COUNT(*) FROM ({STOCK_LEVEL_SELECT} WHERE a.active = 1 AND {LOW_STOCK_CONDITION})"
+    );
+    conn.query_row(&sql, [], |r| r.get(0))
+}
+
+fn insert_movement(
+    conn: &Connection,
+    article_id: i64,
+    kind: &str,
+    quantity: i64,
+    order_id: Option<i64>,
+    note: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        r#"
+        INSERT INTO stock_movements (article_id, kind, quantity, order_id, note)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![article_id, kind, quantity, order_id, note],
+    )?;
+    Ok(())
+}
+
+// (article_id, quantity) pairs from a query taking the order id as ?1
+fn article_quantities(
+    conn: &Connection,
+    sql: &str,
+    order_id: i64,
+) -> Result<Vec<(i64, i64)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([order_id], |r| Ok((r.get(0)?, r.get(1)?)))?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r?); }
+    Ok(out)
+}
+
+fn order_has_movement(conn: &Connection, order_id: i64, kind: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        r#"SELECT EXISTS(SELECT 1 FROM stock_movements WHERE order_id = ?1 AND kind = ?2)"#,
+        params![order_id, kind],
+        |r| r.get(0),
+    )
+}
+
+/// Reserve the order's catalog items, unless it already holds a reservation or has shipped.
+pub(crate) fn reserve_order_stock(conn: &Connection, order_id: i64) -> Result<(), rusqlite::Error> {
+    if order_has_movement(conn, order_id, "shipment")? {
+        return Ok(());
+    }
+    let outstanding = outstanding_reservations(conn, order_id)?;
+    if !outstanding.is_empty() {
+        return Ok(());
+    }
+    let items = article_quantities(conn, ORDER_ARTICLES, order_id)?;
+    for (article_id, qty) in items {
+        insert_movement(conn, article_id, "reservation", qty, Some(order_id), None)?;
+    }
+    Ok(())
+}
+
+fn outstanding_reservations(conn: &Connection, order_id: i64) -> Result<Vec<(i64, i64)>, rusqlite::Error> {
+    article_quantities(
+        conn,
+        r#"
+        SELECT article_id,
+               SUM(CASE kind WHEN 'reservation' THEN quantity ELSE -quantity END) AS held
+        FROM stock_movements
+        WHERE order_id = ?1 AND kind IN ('reservation', 'release')
+        GROUP BY article_id
+        HAVING held > 0
+        "#,
+        order_id,
+    )
+}
+
+/// Give back whatever the order still holds.
+pub(crate) fn release_order_stock(conn: &Connection, order_id: i64) -> Result<(), rusqlite::Error> {
+    for (article_id, qty) in outstanding_reservations(conn, order_id)? {
+        insert_movement(conn, article_id, "release", qty, Some(order_id), None)?;
+    }
+    Ok(())
+}
+
+/// Turn the reservation into a shipment (once per order).
+pub(crate) fn ship_order_stock(conn: &Connection, order_id: i64) -> Result<(), rusqlite::Error> {
+    if order_has_movement(conn, order_id, "shipment")? {
+        return Ok(());
+    }
+    release_order_stock(conn, order_id)?;
+    let items = article_quantities(conn, ORDER_ARTICLES, order_id)?;
+    for (article_id, qty) in items {
+        insert_movement(conn, article_id, "shipment", qty, Some(order_id), None)?;
+    }
+    Ok(())
+}
+
+/// Put returned goods back on the shelf.
+pub(crate) fn restock_returned(
+    conn: &Connection,
+    article_id: i64,
+    quantity: i64,
+    order_id: i64,
+    note: &str,
+) -> Result<(), rusqlite::Error> {
+    insert_movement(conn, article_id, "receipt", quantity, Some(order_id), Some(note))
+}
+
+/// Bring the order's stock movements in line with its (new) status.
+/// Returned goods are not restocked here: whether they go back on the shelf is a separate decision.
+pub(crate) fn sync_order_stock(
+    conn: &Connection,
+    order_id: i64,
+    status: OrderStatus,
+) -> Result<(), rusqlite::Error> {
+    match status {
+        OrderStatus::Pending | OrderStatus::Confirmed => reserve_order_stock(conn, order_id),
+        OrderStatus::WithCourier | OrderStatus::Delivered => ship_order_stock(conn, order_id),
+        OrderStatus::Cancelled => release_order_stock(conn, order_id),
+        OrderStatus::Returned => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_stock_levels(
+    state: tauri::State<AppState>,
+    low_only: Option<bool>,
+) -> Result<Vec<StockLevel>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+    load_stock_levels(&conn, low_only.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_stock_movements(
+    state: tauri::State<AppState>,
+    article_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<StockMovement>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, article_id, kind, quantity, order_id, note, created_at
+            FROM stock_movements
+            WHERE article_id = ?1
+            ORDER BY id DESC
+            LIMIT ?2
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![article_id, limit.unwrap_or(100).max(1)], |row| {
+            Ok(StockMovement {
+                id: row.get(0)?,
+                article_id: row.get(1)?,
+                kind: row.get(2)?,
+                quantity: row.get(3)?,
+                order_id: row.get(4)?,
+                note: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+/// Record goods received or a manual correction (e.g. after a stock count).
+#[tauri::command]
+pub fn record_stock_movement(
+    state: tauri::State<AppState>,
+    movement: StockMovementInput,
+) -> Result<i64, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    match movement.kind.as_str() {
+        "receipt" if movement.quantity <= 0 => {
+            return Err("Received quantity must be positive".to_string())
+        }
+        "receipt" => {}
+        "adjustment" if movement.quantity == 0 => {
+            return Err("Adjustment quantity cannot be zero".to_string())
+        }
+        "adjustment" => {}
+        other => {
+            return Err(format!(
+                "Unsupported stock movement '{other}': reservations and shipments follow orders"
+            ))
+        }
+    }
+
+    let exists = conn
+        .query_row(
+            r#"SELECT EXISTS(SELECT 1 FROM articles WHERE id = ?1)"#,
+            [movement.article_id],
+            |r| r.get::<_, bool>(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Article {} not found", movement.article_id));
+    }
+
+    let note = movement.note.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    insert_movement(&conn, movement.article_id, &movement.kind, movement.quantity, None, note)
+        .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub fn set_low_stock_threshold(
+    state: tauri::State<AppState>,
+    article_id: i64,
+    threshold: Option<i64>,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    if matches!(threshold, Some(t) if t < 0) {
+        return Err("Low-stock threshold cannot be negative".to_string());
+    }
+    let changed = conn
+        .execute(
+            r#"
+            UPDATE articles
+            SET low_stock_threshold = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE id = ?2
+            "#,
+            params![threshold, article_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Article {article_id} not found"));
+    }
+    Ok(())
+}
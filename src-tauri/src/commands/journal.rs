@@ -0,0 +1,467 @@
+// src/commands/journal.rs
+// Undo/redo for order changes. Journaled commands store the order as it was
+// before and after the change; undo puts the "before" state back, redo the
+// "after" one. Returns, exchanges and delivery attempts record what the client
+// or courier did and are not journaled.
+use crate::app_state::AppState;
+use crate::commands::inventory::{release_order_stock, sync_order_stock};
+use crate::commands::orders::record_status_event;
+use crate::db::{ensure_schema, open_db, record_order_event, undo_depth};
+use crate::models::journal::JournalEntry;
+use crate::models::orders::OrderStatus;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, TransactionBehavior};
+use serde_json::{json, Map, Value as Json};
+use tauri::Emitter;
+
+// Everything a journaled command may change on the order row
+const STATE_COLUMNS: [&str; 21] = [
+    "client_name", "article_name", "phone", "phone_raw", "city", "address",
+    "delivery_company_id", "delivery_company", "delivery_date", "description",
+    "status", "done", "completed_at", "subtotal", "delivery_fee", "discount",
+    "amount_to_collect", "currency", "tracking_number", "customer_id", "deleted_at",
+];
+
+const ITEM_COLUMNS: [&str; 7] =
+    ["id", "position", "article_id", "article_name", "quantity", "unit_price", "note"];
+
+fn to_json(v: Value) -> Json {
+    match v {
+        Value::Integer(i) => json!(i),
+        Value::Real(f) => json!(f),
+        Value::Text(s) => json!(s),
+        Value::Null | Value::Blob(_) => Json::Null,
+    }
+}
+
+fn to_sql(v: &Json) -> Value {
+    match v {
+        Json::Bool(b) => Value::Integer(*b as i64),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::Text(s.clone()),
+        _ => Value::Null,
+    }
+}
+
+fn row_to_map(row: &rusqlite::Row, columns: &[&str]) -> rusqlite::Result<Map<String, Json>> {
+    let mut m = Map::new();
+    for (i, col) in columns.iter().enumerate() {
+        m.insert(col.to_string(), to_json(row.get(i)?));
+    }
+    Ok(m)
+}
+
+/// The order row and its items as JSON, trashed or not; None once purged.
+pub(crate) fn capture_order_state(conn: &Connection, id: i64) -> Result<Option<Json>, rusqlite::Error> {
+    let sql = format!("SELECT {} FROM orders WHERE id = ?1", STATE_COLUMNS.join(", "));
+    let order = conn
+        .query_row(&sql, [id], |row| row_to_map(row, &STATE_COLUMNS))
+        .optional()?;
+    let Some(order) = order else { return Ok(None) };
+
+    let sql = format!(
+        "SELECT {} FROM order_items WHERE order_id = ?1 ORDER BY position ASC, id ASC",
+        ITEM_COLUMNS.join(", ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([id], |row| row_to_map(row, &ITEM_COLUMNS))?;
+    let mut items = Vec::new();
+    for r in rows { items.push(Json::Object(r?)); }
+
+    Ok(Some(json!({ "order": order, "items": items })))
+}
+
+// Put a captured state back; None (the order did not exist yet) sends it to the trash
+fn apply_order_state(conn: &Connection, id: i64, state: Option<&Json>) -> Result<(), rusqlite::Error> {
+    let Some(state) = state else {
+        conn.execute(
+            r#"UPDATE orders SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE id = ?1"#,
+            [id],
+        )?;
+        return release_order_stock(conn, id);
+    };
+
+    let order = &state["order"];
+    let status_before: Option<String> = conn
+        .query_row(r#"SELECT status FROM orders WHERE id = ?1"#, [id], |r| r.get(0))
+        .optional()?;
+    let assignments: Vec<String> = STATE_COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, col)| format!("{col} = ?{}", i + 2))
+        .collect();
+    let sql = format!("UPDATE orders SET {} WHERE id = ?1", assignments.join(", "));
+    let values = std::iter::once(Value::Integer(id))
+        .chain(STATE_COLUMNS.iter().map(|col| to_sql(&order[*col])));
+    conn.execute(&sql, params_from_iter(values))?;
+
+    let from = status_before.as_deref().and_then(OrderStatus::parse);
+    let to = order["status"].as_str().and_then(OrderStatus::parse);
+    if let (Some(from), Some(to)) = (from, to) {
+        if from != to {
+            record_status_event(conn, id, from, to)?;
+        }
+    }
+
+    // Items keep their ids so return records still point at them
+    let current = capture_order_state(conn, id)?;
+    if current.as_ref().map(|c| &c["items"]) != Some(&state["items"]) {
+        conn.execute(r#"DELETE FROM order_items WHERE order_id = ?1"#, [id])?;
+        let sql = format!(
+            "INSERT INTO order_items (order_id, {}) VALUES (?1, {})",
+            ITEM_COLUMNS.join(", "),
+            (2..=ITEM_COLUMNS.len() + 1).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", ")
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        for item in state["items"].as_array().into_iter().flatten() {
+            let values = std::iter::once(Value::Integer(id))
+                .chain(ITEM_COLUMNS.iter().map(|col| to_sql(&item[*col])));
+            stmt.execute(params_from_iter(values))?;
+        }
+    }
+
+    release_order_stock(conn, id)?;
+    if order["deleted_at"].is_null() {
+        let status = order["status"].as_str().and_then(OrderStatus::parse).unwrap_or_default();
+        sync_order_stock(conn, id, status)?;
+    }
+    Ok(())
+}
+
+/// Journal a change made by `action`, given the state captured before it.
+/// Call inside the command's transaction, after the change.
+pub(crate) fn record_change(
+    conn: &Connection,
+    order_id: i64,
+    action: &str,
+    before: Option<Json>,
+) -> Result<(), rusqlite::Error> {
+    record_batch_change(conn, None, order_id, action, before).map(|_| ())
+}
+
+/// `record_change` for one order of a multi-order command: entries sharing a
+/// batch are undone and redone as one step. Pass None for the first order and
+/// the returned batch id for the others (None until something was journaled).
+pub(crate) fn record_batch_change(
+    conn: &Connection,
+    batch: Option<i64>,
+    order_id: i64,
+    action: &str,
+    before: Option<Json>,
+) -> Result<Option<i64>, rusqlite::Error> {
+    let depth = undo_depth(conn)?;
+    if depth == 0 {
+        return Ok(batch);
+    }
+    let Some(after) = capture_order_state(conn, order_id)? else { return Ok(batch) };
+    if before.as_ref() == Some(&after) {
+        return Ok(batch);
+    }
+
+    // A new change makes the undone ones unreachable
+    conn.execute(r#"DELETE FROM undo_journal WHERE undone = 1"#, [])?;
+    conn.execute(
+        r#"
+        INSERT INTO undo_journal (order_id, action, before_state, after_state, batch_id)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        params![order_id, action, before.map(|b| b.to_string()), after.to_string(), batch],
+    )?;
+    let batch = batch.unwrap_or_else(|| conn.last_insert_rowid());
+    conn.execute(r#"UPDATE undo_journal SET batch_id = id WHERE batch_id IS NULL"#, [])?;
+    // Keep the latest `depth` steps; a batch counts as one
+    conn.execute(
+        r#"
+        DELETE FROM undo_journal
+        WHERE batch_id NOT IN (
+          SELECT batch_id FROM undo_journal GROUP BY batch_id ORDER BY batch_id DESC LIMIT ?1
+        )
+        "#,
+        [depth],
+    )?;
+    Ok(Some(batch))
+}
+
+// Outcome of replaying one journal step
+enum Replay {
+    Applied(JournalEntry),
+    // An order changed since; the step was dropped from the journal
+    Stale(String),
+}
+
+// Undo (or redo) the latest step inside `conn`'s transaction; None when there is none
+fn replay_step(conn: &Connection, undo: bool) -> Result<Option<Replay>, String> {
+    // Undo walks down from the latest step; redo takes back the last undone one
+    let sql = if undo {
+        r#"SELECT MAX(batch_id) FROM undo_journal WHERE undone = 0"#
+    } else {
+        r#"SELECT MIN(batch_id) FROM undo_journal WHERE undone = 1"#
+    };
+    let batch: Option<i64> = conn.query_row(sql, [], |r| r.get(0)).map_err(|e| e.to_string())?;
+    let Some(batch) = batch else { return Ok(None) };
+
+    // Undo takes a batch back last order first; redo replays it in order
+    let sql = format!(
+        "SELECT id, order_id, action, created_at, before_state, after_state
+         FROM undo_journal WHERE batch_id = ?1 ORDER BY id {}",
+        if undo { "DESC" } else { "ASC" }
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([batch], |row| {
+            Ok((
+                JournalEntry {
+                    id: row.get(0)?,
+                    order_id: row.get(1)?,
+                    order_ids: Vec::new(),
+                    action: row.get(2)?,
+                    created_at: row.get(3)?,
+                },
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let parse = |s: &str| serde_json::from_str::<Json>(s).map_err(|e| e.to_string());
+    let mut steps = Vec::new();
+    for r in rows {
+        let (entry, before, after) = r.map_err(|e| e.to_string())?;
+        let before = before.as_deref().map(parse).transpose()?;
+        steps.push((entry, before, parse(&after)?));
+    }
+
+    // Only replay onto the exact states the step left (or found) the orders in
+    for (entry, before, after) in &steps {
+        let expected = if undo { Some(after) } else { before.as_ref() };
+        let current = capture_order_state(conn, entry.order_id).map_err(|e| e.to_string())?;
+        let in_place = match expected {
+            Some(expected) => current.as_ref() == Some(expected),
+            None => current.is_some_and(|c| !c["order"]["deleted_at"].is_null()),
+        };
+        if !in_place {
+            conn.execute(r#"DELETE FROM undo_journal WHERE batch_id = ?1"#, [batch])
+                .map_err(|e| e.to_string())?;
+            return Ok(Some(Replay::Stale(format!(
+                "Order {} was changed since; '{}' can no longer be {}",
+                entry.order_id,
+                entry.action,
+                if undo { "undone" } else { "redone" }
+            ))));
+        }
+    }
+
+    for (entry, before, after) in &steps {
+        let target = if undo { before.as_ref() } else { Some(after) };
+        apply_order_state(conn, entry.order_id, target).map_err(|e| e.to_string())?;
+        record_order_event(
+            conn,
+            entry.order_id,
+            if undo { "undo" } else { "redo" },
+            None,
+            Some(&entry.action),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    conn.execute(
+        r#"UPDATE undo_journal SET undone = ?1 WHERE batch_id = ?2"#,
+        params![undo, batch],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Report the step by its first entry
+    let mut order_ids: Vec<i64> = steps.iter().map(|(e, _, _)| e.order_id).collect();
+    if undo {
+        order_ids.reverse();
+    }
+    let Some(mut head) = steps.into_iter().map(|(e, _, _)| e).min_by_key(|e| e.id) else { return Ok(None) };
+    head.order_ids = order_ids;
+    Ok(Some(Replay::Applied(head)))
+}
+
+fn replay(app: &tauri::AppHandle, state: &AppState, undo: bool) -> Result<Option<JournalEntry>, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let replayed = replay_step(&tx, undo)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    match replayed {
+        None => Ok(None),
+        Some(Replay::Stale(message)) => Err(message),
+        Some(Replay::Applied(entry)) => {
+            let _ = app.emit(
+                "orders:changed",
+                json!({
+                    "orderId": entry.order_id,
+                    "orderIds": entry.order_ids,
+                    "action": entry.action,
+                    "undone": undo
+                }),
+            );
+            Ok(Some(entry))
+        }
+    }
+}
+
+/// Revert the latest journaled change (a whole bulk action at once); None when there is nothing to undo.
+#[tauri::command]
+pub fn undo_last(
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<Option<JournalEntry>, String> {
+    replay(&app, &state, true)
+}
+
+/// Re-apply the last undone change; None when there is nothing to redo.
+#[tauri::command]
+pub fn redo_last(
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<Option<JournalEntry>, String> {
+    replay(&app, &state, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_values_round_trip_through_json() {
+        for v in [Value::Integer(-42), Value::Text("Mug ×2".into()), Value::Null, Value::Real(1.5)] {
+            assert_eq!(to_sql(&to_json(v.clone())), v);
+        }
+        // Blobs are never snapshotted; booleans come back as SQLite integers
+        assert_eq!(to_json(Value::Blob(vec![1, 2])), Json::Null);
+        assert_eq!(to_sql(&json!(true)), Value::Integer(1));
+        assert_eq!(to_sql(&json!([1, 2])), Value::Null);
+    }
+
+    #[test]
+    fn restoring_a_snapshot_records_the_status_move() {
+        use crate::commands::orders::{change_status, insert_order};
+        let conn = crate::db::open_test_db();
+        let order = serde_json::from_value(json!({
+            "clientName": "Sara", "articleName": "Mug", "phone": "0612345678", "city": "Rabat",
+            "address": "1 Rue Test", "deliveryCompany": "Amana", "deliveryDate": "2026-10-20"
+        }))
+        .unwrap();
+        let id = insert_order(&conn, &order).unwrap();
+        change_status(&conn, id, OrderStatus::Confirmed).unwrap();
+        change_status(&conn, id, OrderStatus::WithCourier).unwrap();
+        let before = capture_order_state(&conn, id).unwrap();
+        change_status(&conn, id, OrderStatus::Delivered).unwrap();
+        let after = capture_order_state(&conn, id).unwrap();
+
+        let last_status_event = || -> (String, String) {
+            conn.query_row(
+                "SELECT old_value, new_value FROM order_events WHERE order_id = ?1 AND kind = 'status' ORDER BY id DESC",
+                [id],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap()
+        };
+        let status_events = || -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM order_events WHERE kind = 'status'", [], |r| r.get(0)).unwrap()
+        };
+
+        // Undo: an undone delivery no longer counts as the latest move
+        apply_order_state(&conn, id, before.as_ref()).unwrap();
+        assert_eq!(last_status_event(), ("delivered".to_string(), "with_courier".to_string()));
+        // Redo
+        apply_order_state(&conn, id, after.as_ref()).unwrap();
+        assert_eq!(last_status_event(), ("with_courier".to_string(), "delivered".to_string()));
+        // Same status: nothing to record
+        let n = status_events();
+        apply_order_state(&conn, id, after.as_ref()).unwrap();
+        assert_eq!(status_events(), n);
+    }
+
+    fn new_order() -> crate::models::orders::NewOrderInput {
+        serde_json::from_value(json!({
+            "clientName": "Sara", "articleName": "Mug", "phone": "0612345678", "city": "Rabat",
+            "address": "1 Rue Test", "deliveryCompany": "Amana", "deliveryDate": "2026-10-20"
+        }))
+        .unwrap()
+    }
+
+    fn delivery_date(conn: &Connection, id: i64) -> String {
+        conn.query_row("SELECT delivery_date FROM orders WHERE id = ?1", [id], |r| r.get(0)).unwrap()
+    }
+
+    // Reschedule every order as one batch, the way bulk actions journal
+    fn reschedule(conn: &Connection, ids: &[i64], date: &str) {
+        let mut batch = None;
+        for &id in ids {
+            let before = capture_order_state(conn, id).unwrap();
+            conn.execute("UPDATE orders SET delivery_date = ?1 WHERE id = ?2", params![date, id]).unwrap();
+            batch = record_batch_change(conn, batch, id, "bulk_reschedule_orders", before).unwrap();
+        }
+    }
+
+    fn applied(step: Option<Replay>) -> JournalEntry {
+        match step {
+            Some(Replay::Applied(entry)) => entry,
+            _ => panic!("expected the step to be replayed"),
+        }
+    }
+
+    #[test]
+    fn a_batch_is_undone_and_redone_as_one_step() {
+        use crate::commands::orders::insert_order;
+        let conn = crate::db::open_test_db();
+        let ids: Vec<i64> = (0..3).map(|_| insert_order(&conn, &new_order()).unwrap()).collect();
+        reschedule(&conn, &ids, "2026-11-05");
+
+        let entry = applied(replay_step(&conn, true).unwrap());
+        assert_eq!(entry.action, "bulk_reschedule_orders");
+        assert_eq!(entry.order_ids, ids);
+        assert!(ids.iter().all(|&id| delivery_date(&conn, id) == "2026-10-20"));
+
+        applied(replay_step(&conn, false).unwrap());
+        assert!(ids.iter().all(|&id| delivery_date(&conn, id) == "2026-11-05"));
+    }
+
+    #[test]
+    fn the_undo_depth_counts_batches_not_orders() {
+        use crate::commands::orders::insert_order;
+        let conn = crate::db::open_test_db();
+        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES ('undoDepth', '2')", []).unwrap();
+        let ids: Vec<i64> = (0..5).map(|_| insert_order(&conn, &new_order()).unwrap()).collect();
+        reschedule(&conn, &ids, "2026-11-01");
+        reschedule(&conn, &ids[..1], "2026-11-02");
+        reschedule(&conn, &ids, "2026-11-03");
+
+        assert_eq!(applied(replay_step(&conn, true).unwrap()).order_ids.len(), 5);
+        assert_eq!(delivery_date(&conn, ids[0]), "2026-11-02");
+        assert!(ids[1..].iter().all(|&id| delivery_date(&conn, id) == "2026-11-01"));
+        assert_eq!(applied(replay_step(&conn, true).unwrap()).order_ids, vec![ids[0]]);
+        // The oldest batch fell off the journal
+        assert!(replay_step(&conn, true).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_batch_with_a_changed_order_is_dropped_whole() {
+        use crate::commands::orders::insert_order;
+        let conn = crate::db::open_test_db();
+        let ids: Vec<i64> = (0..2).map(|_| insert_order(&conn, &new_order()).unwrap()).collect();
+        reschedule(&conn, &ids, "2026-11-05");
+        conn.execute("UPDATE orders SET city = 'Fes' WHERE id = ?1", [ids[1]]).unwrap();
+
+        match replay_step(&conn, true).unwrap() {
+            Some(Replay::Stale(message)) => assert_eq!(
+                message,
+                format!("Order {} was changed since; 'bulk_reschedule_orders' can no longer be undone", ids[1])
+            ),
+            _ => panic!("expected a stale step"),
+        }
+        assert_eq!(delivery_date(&conn, ids[0]), "2026-11-05");
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM undo_journal WHERE action LIKE 'bulk%'", [], |r| r.get(0)).unwrap();
+        assert_eq!(left, 0);
+    }
+}
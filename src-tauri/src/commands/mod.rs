@@ -1,8 +1,18 @@
 // src/commands/mod.rs
 pub mod orders;
+pub mod bulk;
 pub mod opened_orders;
 pub mod search;
+pub mod articles;
+pub mod inventory;
+pub mod settlements;
+pub mod deliveries;
+pub mod returns;
+pub mod journal;
 pub mod companies;
 pub mod settings;
 pub mod theme;
 pub mod dashboard;
+pub mod customers;
+pub mod views;
+pub mod import;
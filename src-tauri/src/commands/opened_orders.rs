@@ -2,7 +2,22 @@
 use crate::app_state::AppState;
 use crate::db::{ensure_schema, open_db};
 use crate::models::orders::OpenedOrderItem;
-use rusqlite::{params, TransactionBehavior};
+use rusqlite::{params, Connection, TransactionBehavior};
+
+// Insert only if missing; append to the end by using MAX(position)+1.
+// The MAX sits in a subquery: as a top-level aggregate it would yield a row
+// even when the order is already open. Returns false in that case.
+pub(crate) fn append_opened_order(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let inserted = conn.execute(
+        r#"
+        INSERT INTO opened_orders (order_id, position)
+        SELECT ?1, COALESCE((SELECT MAX(position) FROM opened_orders), 0) + 1
+        WHERE NOT EXISTS (SELECT 1 FROM opened_orders WHERE order_id = ?1)
+        "#,
+        params![id],
+    )?;
+    Ok(inserted > 0)
+}
 
 /// Open an order without reordering the list:
 /// - If the order is already present, leave its position as-is.
@@ -16,17 +31,7 @@ pub fn open_order(state: tauri::State<AppState>, id: i64) -> Result<(), String>
         .transaction_with_behavior(TransactionBehavior::Immediate)
         .map_err(|e| e.to_string())?;
 
-    // Insert only if missing; append to the end by using MAX(position)+1.
-    tx.execute(
-        r#"
-        INSERT INTO opened_orders (order_id, position)
-        SELECT ?1, COALESCE(MAX(position), 0) + 1
-        FROM opened_orders
-        WHERE NOT EXISTS (SELECT 1 FROM opened_orders WHERE order_id = ?1)
-        "#,
-        params![id],
-    )
-    .map_err(|e| e.to_string())?;
+    append_opened_order(&tx, id).map_err(|e| e.to_string())?;
 
     tx.commit().map_err(|e| e.to_string())?;
     Ok(())
@@ -40,9 +45,11 @@ pub fn get_opened_orders(state: tauri::State<AppState>) -> Result<Vec<OpenedOrde
     let mut stmt = conn
         .prepare(
             r#"
-            SELECT oo.order_id, o.article_name, oo.position
+            SELECT oo.order_id, o.article_name,
+                   (SELECT COUNT(*) FROM order_items i WHERE i.order_id = oo.order_id) AS item_count,
+                   oo.position
             FROM opened_orders oo
-            JOIN orders o ON o.id = oo.order_id
+            JOIN active_orders o ON o.id = oo.order_id
             ORDER BY oo.position ASC
             "#,
         )
@@ -53,7 +60,8 @@ pub fn get_opened_orders(state: tauri::State<AppState>) -> Result<Vec<OpenedOrde
             Ok(OpenedOrderItem {
                 order_id: row.get(0)?,
                 article_name: row.get(1)?,
-                position: row.get(2)?,
+                item_count: row.get(2)?,
+                position: row.get(3)?,
             })
         })
         .map_err(|e| e.to_string())?;
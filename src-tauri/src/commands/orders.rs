@@ -1,41 +1,417 @@
 // src/commands/orders.rs
 use crate::app_state::AppState;
-use crate::db::{ensure_schema, get_or_create_delivery_company, open_db};
-use crate::models::orders::{NewOrderInput, UpdateOrderInput, OrderListItem, OrderWithId};
-use rusqlite::{params};
+use crate::commands::companies::{delivery_terms, tracking_link};
+use crate::commands::inventory::{release_order_stock, reserve_order_stock, sync_order_stock};
+use crate::commands::journal::{capture_order_state, record_change};
+use crate::db::{
+    default_country_code, default_currency, ensure_schema, find_article, get_or_create_delivery_company, open_db,
+    next_order_reference, record_order_event, upsert_customer,
+};
+use crate::models::orders::{
+    DeletedOrder, NewOrderInput, OrderEvent, OrderFilter, OrderItem, OrderItemInput, OrderListItem,
+    OrderPage, OrderRow, OrderSort, OrderSortKey, OrderStatus,
+    OrderWithId, StatusError, UpdateOrderInput,
+};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, TransactionBehavior};
+use crate::money::{compute_totals, OrderTotals};
+use crate::phone::normalize_phone;
+use crate::query::TEXT_SEARCH_COLUMNS;
+use crate::util::escape_like;
+use serde_json::{json, Map, Value as Json};
+
+// Row shape of `OrderListItem`; callers add FROM/WHERE/ORDER BY on alias `o`
+pub(crate) const ORDER_LIST_SELECT: &str = r#"
+    SELECT o.id, COALESCE(o.reference, ''), o.article_name,
+           (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.id) AS item_count,
+           o.done, o.status
+"#;
+
+pub(crate) fn order_list_item_from_row(row: &Row) -> rusqlite::Result<OrderListItem> {
+    Ok(OrderListItem {
+        id: row.get(0)?,
+        reference: row.get(1)?,
+        article_name: row.get(2)?,
+        item_count: row.get(3)?,
+        done: row.get(4)?,
+        status: OrderStatus::parse(&row.get::<_, String>(5)?).unwrap_or_default(),
+    })
+}
+
+// Turn the incoming items (or the legacy single `article_name`) into a validated list
+fn resolve_items(article_name: &str, items: &[OrderItemInput]) -> Result<Vec<OrderItemInput>, String> {
+    let mut out: Vec<OrderItemInput> = Vec::new();
+
+    if items.is_empty() {
+        let name = article_name.trim();
+        if !name.is_empty() {
+            out.push(OrderItemInput {
+                article_id: None,
+                article_name: name.to_string(),
+                quantity: 1,
+                unit_price: None,
+                note: None,
+            });
+        }
+    } else {
+        for (i, it) in items.iter().enumerate() {
+            let name = it.article_name.trim();
+            if name.is_empty() {
+                return Err(format!("Item {}: article name is required", i + 1));
+            }
+            if it.quantity < 1 {
+                return Err(format!("Item {}: quantity must be at least 1", i + 1));
+            }
+            if matches!(it.unit_price, Some(p) if p < 0) {
+                return Err(format!("Item {}: unit price cannot be negative", i + 1));
+            }
+            out.push(OrderItemInput {
+                article_id: it.article_id,
+                article_name: name.to_string(),
+                quantity: it.quantity,
+                unit_price: it.unit_price,
+                note: it.note.as_ref().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            });
+        }
+    }
+
+    if out.is_empty() {
+        return Err("An order needs at least one article".to_string());
+    }
+    Ok(out)
+}
+
+// Link items to the catalog: canonical spelling and default price when none was given
+fn apply_catalog(conn: &Connection, items: &mut [OrderItemInput]) -> Result<(), String> {
+    for it in items.iter_mut() {
+        let found = match it.article_id {
+            Some(aid) => {
+                let row: Option<(i64, String, Option<i64>)> = conn
+                    .query_row(
+                        r#"SELECT id, name, default_price FROM articles WHERE id = ?1"#,
+                        [aid],
+                        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?;
+                Some(row.ok_or_else(|| format!("Article {aid} not found"))?)
+            }
+            None => find_article(conn, &it.article_name).map_err(|e| e.to_string())?,
+        };
+        if let Some((aid, name, default_price)) = found {
+            it.article_id = Some(aid);
+            it.article_name = name;
+            if it.unit_price.is_none() {
+                it.unit_price = default_price;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Totals from the priced items (after catalog defaults) and the order-level fee/discount
+fn order_totals(
+    items: &[OrderItemInput],
+    delivery_fee: Option<i64>,
+    discount: Option<i64>,
+) -> Result<OrderTotals, String> {
+    compute_totals(
+        items.iter().map(|it| (it.quantity, it.unit_price)),
+        delivery_fee.unwrap_or(0),
+        discount.unwrap_or(0),
+    )
+}
+
+// Fill what the form left empty from the company's rate card: the fee for the
+// city and a delivery date after the promised number of days.
+// Coverage is only advisory here; the form shows `suggest_delivery` warnings.
+fn delivery_defaults(
+    conn: &Connection,
+    company_id: i64,
+    city: &str,
+    delivery_fee: Option<i64>,
+    delivery_date: &str,
+) -> Result<(Option<i64>, String), String> {
+    if delivery_fee.is_some() && !delivery_date.trim().is_empty() {
+        return Ok((delivery_fee, delivery_date.trim().to_string()));
+    }
+    let terms = delivery_terms(conn, Some(company_id), city).map_err(|e| e.to_string())?;
+    let date = match delivery_date.trim() {
+        "" => terms.delivery_date.unwrap_or_default(),
+        d => d.to_string(),
+    };
+    Ok((delivery_fee.or(terms.fee), date))
+}
+
+// Display label stored in orders.article_name, e.g. "Mug ×2, T-Shirt"
+fn items_summary(items: &[OrderItemInput]) -> String {
+    items
+        .iter()
+        .map(|it| {
+            if it.quantity > 1 {
+                format!("{} ×{}", it.article_name, it.quantity)
+            } else {
+                it.article_name.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Replace all items of an order (caller owns the transaction)
+fn replace_order_items(
+    conn: &Connection,
+    order_id: i64,
+    items: &[OrderItemInput],
+) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM order_items WHERE order_id = ?1", params![order_id])?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT INTO order_items (order_id, position, article_id, article_name, quantity, unit_price, note)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+    )?;
+    for (i, it) in items.iter().enumerate() {
+        stmt.execute(params![
+            order_id,
+            i as i64,
+            it.article_id,
+            it.article_name,
+            it.quantity,
+            it.unit_price,
+            it.note
+        ])?;
+    }
+    Ok(())
+}
+
+pub(crate) fn load_order_items(conn: &Connection, order_id: i64) -> Result<Vec<OrderItem>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, article_id, article_name, quantity, unit_price, note
+        FROM order_items
+        WHERE order_id = ?1
+        ORDER BY position ASC, id ASC
+        "#,
+    )?;
+    let rows = stmt.query_map([order_id], |row| {
+        Ok(OrderItem {
+            id: row.get(0)?,
+            article_id: row.get(1)?,
+            article_name: row.get(2)?,
+            quantity: row.get(3)?,
+            unit_price: row.get(4)?,
+            note: row.get(5)?,
+        })
+    })?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r?); }
+    Ok(out)
+}
+
+// Editable fields of an order as JSON, used for edit/delete history
+fn order_snapshot(conn: &Connection, id: i64) -> Result<Option<Map<String, Json>>, rusqlite::Error> {
+    let base: Option<Map<String, Json>> = conn
+        .query_row(
+            r#"
+            SELECT client_name, phone, city, address, delivery_company, delivery_date, description,
+                   delivery_fee, discount, amount_to_collect, tracking_number
+            FROM active_orders
+            WHERE id = ?1
+            "#,
+            [id],
+            |row| {
+                let mut m = Map::new();
+                m.insert("clientName".into(), json!(row.get::<_, String>(0)?));
+                m.insert("phone".into(), json!(row.get::<_, String>(1)?));
+                m.insert("city".into(), json!(row.get::<_, String>(2)?));
+                m.insert("address".into(), json!(row.get::<_, String>(3)?));
+                m.insert("deliveryCompany".into(), json!(row.get::<_, String>(4)?));
+                m.insert("deliveryDate".into(), json!(row.get::<_, String>(5)?));
+                m.insert("description".into(), json!(row.get::<_, Option<String>>(6)?));
+                m.insert("deliveryFee".into(), json!(row.get::<_, i64>(7)?));
+                m.insert("discount".into(), json!(row.get::<_, i64>(8)?));
+                m.insert("amountToCollect".into(), json!(row.get::<_, i64>(9)?));
+                m.insert("trackingNumber".into(), json!(row.get::<_, Option<String>>(10)?));
+                Ok(m)
+            },
+        )
+        .optional()?;
+
+    let Some(mut m) = base else { return Ok(None) };
+    let items = load_order_items(conn, id)?
+        .into_iter()
+        .map(|it| {
+            json!({
+                "articleName": it.article_name,
+                "quantity": it.quantity,
+                "unitPrice": it.unit_price,
+                "note": it.note,
+            })
+        })
+        .collect::<Vec<_>>();
+    m.insert("items".into(), Json::Array(items));
+    Ok(Some(m))
+}
+
+// Keep only the fields that differ between two snapshots
+fn snapshot_diff(before: &Map<String, Json>, after: &Map<String, Json>) -> (Map<String, Json>, Map<String, Json>) {
+    let mut old = Map::new();
+    let mut new = Map::new();
+    for (k, v) in after {
+        let prev = before.get(k).cloned().unwrap_or(Json::Null);
+        if &prev != v {
+            old.insert(k.clone(), prev);
+            new.insert(k.clone(), v.clone());
+        }
+    }
+    (old, new)
+}
+
+// Move an order to another status, stamping completion and recording the change
+// (caller owns the transaction and has already validated the transition).
+pub(crate) fn apply_status(
+    conn: &Connection,
+    id: i64,
+    from: OrderStatus,
+    to: OrderStatus,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        r#"
+        UPDATE orders SET
+          status = ?1,
+          completed_at = CASE
+            WHEN ?1 IN ('delivered', 'returned', 'cancelled')
+              THEN COALESCE(completed_at, strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            ELSE NULL
+          END
+        WHERE id = ?2
+        "#,
+        params![to.as_str(), id],
+    )?;
+    sync_order_stock(conn, id, to)?;
+    record_status_event(conn, id, from, to)
+}
+
+// Status moves feed the scorecards and dashboard, so every path that changes
+// the status (including undo/redo) records one
+pub(crate) fn record_status_event(
+    conn: &Connection,
+    id: i64,
+    from: OrderStatus,
+    to: OrderStatus,
+) -> Result<(), rusqlite::Error> {
+    record_order_event(conn, id, "status", Some(from.as_str()), Some(to.as_str()))
+}
+
+/// CTE `{name}(order_id, {at})`: when each order first reached `status`, from the
+/// status history. A move later taken back (undo, unticked checkbox) does not count:
+/// leaving `status` other than along the lifecycle voids the earlier arrivals.
+pub(crate) fn status_reached_cte(name: &str, at: &str, status: OrderStatus) -> String {
+    let s = status.as_str();
+    let onward: Vec<String> = OrderStatus::ALL
+        .iter()
+        .filter(|next| status.can_transition_to(**next))
+        .map(|next| format!("'{}'", next.as_str()))
+        .collect();
+    format!(
+        r#"
+        {name} AS (
+          SELECT e.order_id, MIN(e.created_at) AS {at}
+          FROM order_events e
+          WHERE e.kind = 'status' AND e.new_value = '{s}'
+            AND NOT EXISTS (
+              SELECT 1 FROM order_events r
+              WHERE r.order_id = e.order_id AND r.kind = 'status' AND r.id > e.id
+                AND r.old_value = '{s}' AND r.new_value NOT IN ({})
+            )
+          GROUP BY e.order_id
+        )"#,
+        onward.join(", ")
+    )
+}
 
 #[tauri::command]
 pub fn save_order(state: tauri::State<AppState>, order: NewOrderInput) -> Result<i64, String> {
-    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let id = insert_order(&tx, &order)?;
+    record_change(&tx, id, "save_order", None).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+// Validate and insert a new pending order with its items, stock reservation and
+// "created" event (caller owns the transaction)
+pub(crate) fn insert_order(tx: &Connection, order: &NewOrderInput) -> Result<i64, String> {
+    let mut items = resolve_items(&order.article_name, &order.items)?;
+    let cc = default_country_code(tx).map_err(|e| e.to_string())?;
+    let phone = normalize_phone(&order.phone, &cc).map_err(|e| e.to_string())?;
+
+    apply_catalog(tx, &mut items)?;
     let (company_id, company_name) =
-        get_or_create_delivery_company(&conn, &order.delivery_company).map_err(|e| e.to_string())?;
+        get_or_create_delivery_company(tx, &order.delivery_company).map_err(|e| e.to_string())?;
+    let (delivery_fee, delivery_date) =
+        delivery_defaults(tx, company_id, &order.city, order.delivery_fee, &order.delivery_date)?;
+    let totals = order_totals(&items, delivery_fee, order.discount)?;
+    let currency = default_currency(tx).map_err(|e| e.to_string())?;
+    let customer_id =
+        upsert_customer(tx, &phone, &order.client_name, &order.city, &order.address)
+            .map_err(|e| e.to_string())?;
+    let year: i32 = tx
+        .query_row(r#"SELECT CAST(strftime('%Y','now','localtime') AS INTEGER)"#, [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    let reference = next_order_reference(tx, year).map_err(|e| e.to_string())?;
 
-    conn.execute(
+    tx.execute(
         r#"
         INSERT INTO orders
           (client_name, article_name, phone, city, address,
-           delivery_company, delivery_company_id, delivery_date, description)
+           delivery_company, delivery_company_id, delivery_date, description,
+           customer_id, phone_raw,
+           subtotal, delivery_fee, discount, amount_to_collect, currency, reference)
         VALUES (?1, ?2, ?3, ?4, ?5,
-                ?6, ?7, ?8, ?9)
+                ?6, ?7, ?8, ?9,
+                ?10, ?11,
+                ?12, ?13, ?14, ?15, ?16, ?17)
         "#,
         params![
             order.client_name,
-            order.article_name,
-            order.phone,
+            items_summary(&items), // denormalized label for lists
+            phone,             // E.164
             order.city,
             order.address,
             company_name,      // normalized display name
             company_id,        // FK
-            order.delivery_date,
-            order.description
+            delivery_date,
+            order.description,
+            customer_id,
+            order.phone.trim(), // as typed
+            totals.subtotal,
+            totals.delivery_fee,
+            totals.discount,
+            totals.amount_to_collect,
+            currency,
+            reference
         ],
     )
     .map_err(|e| e.to_string())?;
-    Ok(conn.last_insert_rowid())
+    let id = tx.last_insert_rowid();
+
+    replace_order_items(tx, id, &items).map_err(|e| e.to_string())?;
+    reserve_order_stock(tx, id).map_err(|e| e.to_string())?;
+    record_order_event(tx, id, "created", None, Some(OrderStatus::Pending.as_str()))
+        .map_err(|e| e.to_string())?;
+    Ok(id)
 }
 
+/// Also finds orders in the trash, so they can be reviewed before a restore; `deletedAt` tells them apart.
 #[tauri::command]
 pub fn get_order(state: tauri::State<AppState>, id: i64) -> Result<OrderWithId, String> {
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
@@ -45,17 +421,23 @@ pub fn get_order(state: tauri::State<AppState>, id: i64) -> Result<OrderWithId,
         .prepare(
             r#"
             SELECT id, client_name, article_name, phone, city, address,
-                   delivery_company, delivery_date, description, done
+                   delivery_company, delivery_date, description, done, status,
+                   COALESCE(phone_raw, phone),
+                   subtotal, delivery_fee, discount, amount_to_collect, currency,
+                   tracking_number, delivery_company_id, COALESCE(reference, ''), deleted_at
             FROM orders
             WHERE id = ?1
             "#,
         )
         .map_err(|e| e.to_string())?;
 
-    let order = stmt
+    let mut company_id: Option<i64> = None;
+    let mut order = stmt
         .query_row([id], |row| {
+            company_id = row.get(18)?;
             Ok(OrderWithId {
                 id: row.get(0)?,
+                reference: row.get(19)?,
                 client_name: row.get(1)?,
                 article_name: row.get(2)?,
                 phone: row.get(3)?,
@@ -65,10 +447,26 @@ pub fn get_order(state: tauri::State<AppState>, id: i64) -> Result<OrderWithId,
                 delivery_date: row.get(7)?,
                 description: row.get(8)?,
                 done: row.get(9)?,
+                status: OrderStatus::parse(&row.get::<_, String>(10)?).unwrap_or_default(),
+                phone_raw: row.get(11)?,
+                items: Vec::new(),
+                subtotal: row.get(12)?,
+                delivery_fee: row.get(13)?,
+                discount: row.get(14)?,
+                amount_to_collect: row.get(15)?,
+                currency: row.get(16)?,
+                tracking_number: row.get(17)?,
+                tracking_url: None,
+                deleted_at: row.get(20)?,
             })
         })
         .map_err(|e| e.to_string())?;
 
+    order.items = load_order_items(&conn, id).map_err(|e| e.to_string())?;
+    if let (Some(cid), Some(tn)) = (company_id, order.tracking_number.as_deref()) {
+        order.tracking_url = tracking_link(&conn, cid, tn).map_err(|e| e.to_string())?;
+    }
+
     Ok(order)
 }
 
@@ -78,13 +476,34 @@ pub fn update_order(
     id: i64,
     order: UpdateOrderInput,
 ) -> Result<(), String> {
-    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
+    let mut items = resolve_items(&order.article_name, &order.items)?;
+    let cc = default_country_code(&conn).map_err(|e| e.to_string())?;
+    let phone = normalize_phone(&order.phone, &cc).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    let before = order_snapshot(&tx, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Order {id} not found"))?;
+
+    apply_catalog(&tx, &mut items)?;
     let (company_id, company_name) =
-        get_or_create_delivery_company(&conn, &order.delivery_company).map_err(|e| e.to_string())?;
+        get_or_create_delivery_company(&tx, &order.delivery_company).map_err(|e| e.to_string())?;
+    let (delivery_fee, delivery_date) =
+        delivery_defaults(&tx, company_id, &order.city, order.delivery_fee, &order.delivery_date)?;
+    // The currency stays the one the order was priced in
+    let totals = order_totals(&items, delivery_fee, order.discount)?;
+    let customer_id =
+        upsert_customer(&tx, &phone, &order.client_name, &order.city, &order.address)
+            .map_err(|e| e.to_string())?;
 
-    conn.execute(
+    tx.execute(
         r#"
         UPDATE orders SET
           client_name = ?1,
@@ -95,68 +514,301 @@ pub fn update_order(
           delivery_company = ?6,
           delivery_company_id = ?7,
           delivery_date = ?8,
-          description = ?9
-        WHERE id = ?10
+          description = ?9,
+          customer_id = ?10,
+          phone_raw = ?11,
+          subtotal = ?12,
+          delivery_fee = ?13,
+          discount = ?14,
+          amount_to_collect = ?15
+        WHERE id = ?16
         "#,
         params![
             order.client_name,
-            order.article_name,
-            order.phone,
+            items_summary(&items), // denormalized label for lists
+            phone,             // E.164
             order.city,
             order.address,
             company_name,      // normalized display name
             company_id,        // FK
-            order.delivery_date,
+            delivery_date,
             order.description,
+            customer_id,
+            order.phone.trim(), // as typed
+            totals.subtotal,
+            totals.delivery_fee,
+            totals.discount,
+            totals.amount_to_collect,
             id
         ],
     )
     .map_err(|e| e.to_string())?;
+
+    replace_order_items(&tx, id, &items).map_err(|e| e.to_string())?;
+    // Re-reserve against the new item list while the order is still open;
+    // once shipped, the stock that left the shelf stays accounted as it was
+    if matches!(current_status(&tx, id)?, OrderStatus::Pending | OrderStatus::Confirmed) {
+        release_order_stock(&tx, id).map_err(|e| e.to_string())?;
+        reserve_order_stock(&tx, id).map_err(|e| e.to_string())?;
+    }
+
+    let after = order_snapshot(&tx, id)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    let (old, new) = snapshot_diff(&before, &after);
+    if !new.is_empty() {
+        record_order_event(
+            &tx,
+            id,
+            "edited",
+            Some(&Json::Object(old).to_string()),
+            Some(&Json::Object(new).to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    record_change(&tx, id, "update_order", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
+pub(crate) fn current_status(conn: &Connection, id: i64) -> Result<OrderStatus, String> {
+    let raw: Option<String> = conn
+        .query_row(r#"SELECT status FROM active_orders WHERE id = ?1"#, [id], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let raw = raw.ok_or_else(|| format!("Order {id} not found"))?;
+    Ok(OrderStatus::parse(&raw).unwrap_or_default())
+}
+
+/// Order list checkbox: checked means delivered, unchecked means pending.
+/// Ticking walks the lifecycle (recording each step), so e.g. a cancelled
+/// order cannot be ticked as delivered.
 #[tauri::command]
 pub fn set_order_done(state: tauri::State<AppState>, id: i64, done: bool) -> Result<(), String> {
-    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
-    conn.execute(
-        r#"UPDATE orders SET done = ?1 WHERE id = ?2"#,
-        params![done, id],
-    )
-    .map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    if !toggle_done(&tx, id, done)? {
+        return Ok(());
+    }
+
+    record_change(&tx, id, "set_order_done", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
+// Apply the checkbox moves one status at a time; false when nothing changed
+pub(crate) fn toggle_done(conn: &Connection, id: i64, done: bool) -> Result<bool, String> {
+    let mut from = current_status(conn, id)?;
+    let path = from.done_toggle_path(done).map_err(|e| e.to_string())?;
+    for &next in &path {
+        apply_status(conn, id, from, next).map_err(|e| e.to_string())?;
+        from = next;
+    }
+    Ok(!path.is_empty())
+}
+
+// Walk the lifecycle to `next`; false when the order already has that status
+pub(crate) fn change_status(conn: &Connection, id: i64, next: OrderStatus) -> Result<bool, String> {
+    let from = current_status(conn, id)?;
+    if from == next {
+        return Ok(false);
+    }
+    if !from.can_transition_to(next) {
+        return Err(StatusError::IllegalTransition { from, to: next }.to_string());
+    }
+    apply_status(conn, id, from, next).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn set_order_status(
+    state: tauri::State<AppState>,
+    id: i64,
+    status: String,
+) -> Result<OrderStatus, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let next = OrderStatus::parse(&status)
+        .ok_or_else(|| StatusError::Unknown(status.clone()).to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    if !change_status(&tx, id, next)? {
+        return Ok(next);
+    }
+
+    record_change(&tx, id, "set_order_status", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(next)
+}
+
+// Move an order to the trash; false when it is missing or already there
+pub(crate) fn trash_order(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
+    let Some(snapshot) = order_snapshot(conn, id)? else { return Ok(false) };
+    record_order_event(conn, id, "deleted", Some(&Json::Object(snapshot).to_string()), None)?;
+    release_order_stock(conn, id)?;
+    conn.execute(
+        r#"UPDATE orders SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE id = ?1"#,
+        params![id],
+    )?;
+    Ok(true)
+}
+
 #[tauri::command]
 pub fn delete_order(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
-    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
-    conn.execute(r#"DELETE FROM orders WHERE id = ?1"#, params![id])
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
         .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    // Already deleted orders are left as they are
+    trash_order(&tx, id).map_err(|e| e.to_string())?;
+
+    record_change(&tx, id, "delete_order", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn list_orders(state: tauri::State<AppState>) -> Result<Vec<OrderListItem>, String> {
+pub fn list_deleted_orders(state: tauri::State<AppState>) -> Result<Vec<DeletedOrder>, String> {
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
     let mut stmt = conn
         .prepare(
             r#"
-            SELECT id, article_name, done
+            SELECT id, COALESCE(reference, ''), client_name, article_name, status, deleted_at
             FROM orders
-            ORDER BY created_at DESC, id DESC
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id DESC
             "#,
         )
         .map_err(|e| e.to_string())?;
 
     let rows = stmt
         .query_map([], |row| {
-            Ok(OrderListItem {
+            Ok(DeletedOrder {
+                id: row.get(0)?,
+                reference: row.get(1)?,
+                client_name: row.get(2)?,
+                article_name: row.get(3)?,
+                status: OrderStatus::parse(&row.get::<_, String>(4)?).unwrap_or_default(),
+                deleted_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+/// Take an order out of the trash, with the status it had when it was deleted.
+#[tauri::command]
+pub fn restore_order(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let journal_before = capture_order_state(&tx, id).map_err(|e| e.to_string())?;
+
+    let status: String = tx
+        .query_row(
+            r#"SELECT status FROM orders WHERE id = ?1 AND deleted_at IS NOT NULL"#,
+            [id],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Order {id} is not in the trash"))?;
+
+    tx.execute(r#"UPDATE orders SET deleted_at = NULL WHERE id = ?1"#, params![id])
+        .map_err(|e| e.to_string())?;
+    let status = OrderStatus::parse(&status).unwrap_or_default();
+    sync_order_stock(&tx, id, status).map_err(|e| e.to_string())?;
+    record_order_event(&tx, id, "restored", None, Some(status.as_str()))
+        .map_err(|e| e.to_string())?;
+
+    record_change(&tx, id, "restore_order", journal_before).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Permanently remove orders deleted before `older_than` (date or ISO timestamp).
+/// Returns how many orders were purged.
+#[tauri::command]
+pub fn purge_deleted_orders(state: tauri::State<AppState>, older_than: String) -> Result<usize, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let older_than = older_than.trim();
+    let valid: bool = conn
+        .query_row(r#"SELECT datetime(?1) IS NOT NULL"#, [older_than], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    if !valid {
+        return Err(format!("Invalid date '{older_than}'"));
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    // Items, history, returns and the opened stack go with the order (ON DELETE CASCADE)
+    let purged = tx
+        .execute(
+            r#"
+            DELETE FROM orders
+            WHERE deleted_at IS NOT NULL
+              AND datetime(deleted_at) < datetime(?1)
+            "#,
+            [older_than],
+        )
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(purged)
+}
+
+#[tauri::command]
+pub fn get_order_history(state: tauri::State<AppState>, id: i64) -> Result<Vec<OrderEvent>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, order_id, kind, old_value, new_value, created_at
+            FROM order_events
+            WHERE order_id = ?1
+            ORDER BY id ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([id], |row| {
+            Ok(OrderEvent {
                 id: row.get(0)?,
-                article_name: row.get(1)?,
-                done: row.get(2)?,
+                order_id: row.get(1)?,
+                kind: row.get(2)?,
+                old_value: row.get(3)?,
+                new_value: row.get(4)?,
+                created_at: row.get(5)?,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -165,3 +817,456 @@ pub fn list_orders(state: tauri::State<AppState>) -> Result<Vec<OrderListItem>,
     for r in rows { out.push(r.map_err(|e| e.to_string())?); }
     Ok(out)
 }
+
+#[tauri::command]
+pub fn list_orders(state: tauri::State<AppState>) -> Result<Vec<OrderListItem>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{ORDER_LIST_SELECT}
+         FROM active_orders o
+         ORDER BY o.created_at DESC, o.id DESC"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], order_list_item_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+const MAX_RELATIVE_DAYS: i64 = 3650;
+
+/// WHERE conditions (on alias `o`, joined with AND) and their positional `?`
+/// parameters for an order filter.
+pub(crate) fn order_filter_sql(
+    conn: &Connection,
+    filter: &OrderFilter,
+) -> Result<(String, Vec<Value>), String> {
+    let non_empty = |v: &Option<String>| v.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+    let mut conds: Vec<String> = vec!["1 = 1".to_string()];
+    let mut args: Vec<Value> = Vec::new();
+
+    if !filter.status.is_empty() {
+        conds.push(format!("o.status IN ({})", vec!["?"; filter.status.len()].join(", ")));
+        args.extend(filter.status.iter().map(|s| Value::Text(s.as_str().to_string())));
+    }
+    if let Some(company_id) = filter.delivery_company_id {
+        conds.push("o.delivery_company_id = ?".to_string());
+        args.push(Value::Integer(company_id));
+    }
+    if let Some(city) = non_empty(&filter.city) {
+        conds.push("o.city = ? COLLATE NOCASE".to_string());
+        args.push(Value::Text(city));
+    }
+
+    let ranges = [
+        (&filter.created_from, "date(o.created_at, 'localtime') >= date(?)"),
+        (&filter.created_to, "date(o.created_at, 'localtime') <= date(?)"),
+        (&filter.delivery_from, "date(o.delivery_date) >= date(?)"),
+        (&filter.delivery_to, "date(o.delivery_date) <= date(?)"),
+    ];
+    for (value, cond) in ranges {
+        let Some(day) = non_empty(value) else { continue };
+        let valid: bool = conn
+            .query_row(r#"SELECT date(?1) IS NOT NULL"#, [&day], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        if !valid {
+            return Err(format!("Invalid date '{day}'"));
+        }
+        conds.push(cond.to_string());
+        args.push(Value::Text(day));
+    }
+
+    if matches!(filter.min_age_days, Some(d) if d < 0) {
+        return Err("Minimum age cannot be negative".to_string());
+    }
+    let relative = [
+        (filter.delivery_from_days, "date(o.delivery_date) >= date('now', 'localtime', ? || ' days')"),
+        (filter.delivery_to_days, "date(o.delivery_date) <= date('now', 'localtime', ? || ' days')"),
+        (filter.min_age_days.map(|d| -d), "date(o.created_at, 'localtime') <= date('now', 'localtime', ? || ' days')"),
+    ];
+    for (days, cond) in relative {
+        let Some(days) = days else { continue };
+        if days.abs() > MAX_RELATIVE_DAYS {
+            return Err(format!("Relative day filters must be within {MAX_RELATIVE_DAYS} days"));
+        }
+        conds.push(cond.to_string());
+        args.push(Value::Integer(days));
+    }
+    if filter.open_only || filter.overdue {
+        conds.push("o.status IN ('pending','confirmed','with_courier')".to_string());
+    }
+    if filter.overdue {
+        conds.push("date(o.delivery_date) < date('now','localtime')".to_string());
+    }
+
+    if let Some(text) = non_empty(&filter.text) {
+        let like: Vec<String> = TEXT_SEARCH_COLUMNS.iter().map(|c| format!("{c} LIKE ? ESCAPE '\\'")).collect();
+        conds.push(format!("({})", like.join(" OR ")));
+        let pat = format!("%{}%", escape_like(&text));
+        args.extend(TEXT_SEARCH_COLUMNS.iter().map(|_| Value::Text(pat.clone())));
+    }
+
+    Ok((conds.join(" AND "), args))
+}
+
+// (ORDER BY expression, value stored in the cursor) per sort key
+fn sort_columns(key: OrderSortKey) -> (&'static str, &'static str) {
+    match key {
+        OrderSortKey::CreatedAt => ("o.created_at", "o.created_at"),
+        OrderSortKey::DeliveryDate => ("o.delivery_date", "o.delivery_date"),
+        OrderSortKey::ClientName => ("o.client_name COLLATE NOCASE", "o.client_name"),
+        OrderSortKey::City => ("o.city COLLATE NOCASE", "o.city"),
+        OrderSortKey::Reference => ("COALESCE(o.reference, '')", "COALESCE(o.reference, '')"),
+    }
+}
+
+/// One page of the order list. Pagination is keyset-based: pass `nextCursor`
+/// back with the same filter and sort to get the following page.
+#[tauri::command]
+pub fn query_orders(
+    state: tauri::State<AppState>,
+    filter: Option<OrderFilter>,
+    sort: Option<OrderSort>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<OrderPage, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    order_page(&conn, &filter.unwrap_or_default(), sort.unwrap_or_default(), cursor, limit)
+}
+
+pub(crate) fn count_orders(conn: &Connection, filter: &OrderFilter) -> Result<i64, String> {
+    let (where_sql, args) = order_filter_sql(conn, filter)?;
+    count_orders_where(conn, &where_sql, &args)
+}
+
+fn count_orders_where(conn: &Connection, where_sql: &str, args: &[Value]) -> Result<i64, String> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM active_orders o WHERE {where_sql}"),
+        params_from_iter(args.iter()),
+        |r| r.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn order_page(
+    conn: &Connection,
+    filter: &OrderFilter,
+    sort: OrderSort,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<OrderPage, String> {
+    let (where_sql, args) = order_filter_sql(conn, filter)?;
+    order_page_where(conn, &where_sql, args, sort, cursor, limit)
+}
+
+/// Like `order_page` for a precompiled condition on `active_orders o`.
+pub(crate) fn order_page_where(
+    conn: &Connection,
+    where_sql: &str,
+    args: Vec<Value>,
+    sort: OrderSort,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<OrderPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let total = count_orders_where(conn, where_sql, &args)?;
+
+    let (order_expr, value_expr) = sort_columns(sort.key);
+    let (dir, op) = if sort.descending { ("DESC", "<") } else { ("ASC", ">") };
+    let mut page_args = args;
+    let mut keyset = String::new();
+    if let Some(cursor) = cursor.as_deref().filter(|c| !c.trim().is_empty()) {
+        let (value, id): (String, i64) =
+            serde_json::from_str(cursor).map_err(|_| "Invalid page cursor".to_string())?;
+        keyset = format!(" AND ({order_expr} {op} ? OR ({order_expr} = ? AND o.id {op} ?))");
+        page_args.extend([Value::Text(value.clone()), Value::Text(value), Value::Integer(id)]);
+    }
+    page_args.push(Value::Integer(limit + 1)); // one extra row tells whether a next page exists
+
+    let sql = format!(
+        "SELECT o.id, COALESCE(o.reference, ''), o.client_name, o.article_name,
+                (SELECT COUNT(*) FROM order_items i WHERE i.order_id = o.id),
+                o.city, o.delivery_date, o.delivery_company, o.status, o.done, o.created_at,
+                {value_expr}
+         FROM active_orders o
+         WHERE {where_sql}{keyset}
+         ORDER BY {order_expr} {dir}, o.id {dir}
+         LIMIT ?"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(page_args.iter()), |row| {
+            Ok((
+                OrderRow {
+                    id: row.get(0)?,
+                    reference: row.get(1)?,
+                    client_name: row.get(2)?,
+                    article_name: row.get(3)?,
+                    item_count: row.get(4)?,
+                    city: row.get(5)?,
+                    delivery_date: row.get(6)?,
+                    delivery_company: row.get(7)?,
+                    status: OrderStatus::parse(&row.get::<_, String>(8)?).unwrap_or_default(),
+                    done: row.get(9)?,
+                    created_at: row.get(10)?,
+                },
+                row.get::<_, String>(11)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+
+    let next_cursor = if out.len() as i64 > limit {
+        out.truncate(limit as usize);
+        out.last().map(|(row, value)| json!([value, row.id]).to_string())
+    } else {
+        None
+    };
+    Ok(OrderPage {
+        rows: out.into_iter().map(|(row, _)| row).collect(),
+        total,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, quantity: i64) -> OrderItemInput {
+        OrderItemInput { article_id: None, article_name: name.into(), quantity, unit_price: None, note: None }
+    }
+
+    #[test]
+    fn resolve_items_falls_back_to_the_legacy_article_name() {
+        let items = resolve_items("  Mug  ", &[]).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].article_name, "Mug");
+        assert_eq!(items[0].quantity, 1);
+        // Items win over the legacy field
+        let items = resolve_items("Ignored", &[item("Cap", 2)]).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].article_name, "Cap");
+    }
+
+    #[test]
+    fn resolve_items_trims_names_and_drops_blank_notes() {
+        let mut it = item(" T-Shirt ", 3);
+        it.note = Some("   ".into());
+        let items = resolve_items("", &[it]).unwrap();
+        assert_eq!(items[0].article_name, "T-Shirt");
+        assert_eq!(items[0].note, None);
+    }
+
+    #[test]
+    fn resolve_items_reports_the_offending_item() {
+        assert_eq!(resolve_items("", &[]).unwrap_err(), "An order needs at least one article");
+        assert_eq!(resolve_items(" ", &[]).unwrap_err(), "An order needs at least one article");
+        assert_eq!(
+            resolve_items("", &[item("Mug", 1), item(" ", 1)]).unwrap_err(),
+            "Item 2: article name is required"
+        );
+        assert_eq!(resolve_items("", &[item("Mug", 0)]).unwrap_err(), "Item 1: quantity must be at least 1");
+        let mut it = item("Mug", 1);
+        it.unit_price = Some(-1);
+        assert_eq!(resolve_items("", &[it]).unwrap_err(), "Item 1: unit price cannot be negative");
+    }
+
+    #[test]
+    fn items_summary_shows_quantities_above_one() {
+        assert_eq!(items_summary(&[item("Mug", 2), item("T-Shirt", 1)]), "Mug ×2, T-Shirt");
+        assert_eq!(items_summary(&[item("Cap", 1)]), "Cap");
+        assert_eq!(items_summary(&[]), "");
+    }
+
+    #[test]
+    fn snapshot_diff_keeps_changed_and_new_fields_only() {
+        let before = json!({ "city": "Rabat", "phone": "+212600000000", "note": "x" });
+        let after = json!({ "city": "Fes", "phone": "+212600000000", "note": "x", "tracking": "AB1" });
+        let (old, new) = snapshot_diff(before.as_object().unwrap(), after.as_object().unwrap());
+        assert_eq!(Json::Object(old), json!({ "city": "Rabat", "tracking": null }));
+        assert_eq!(Json::Object(new), json!({ "city": "Fes", "tracking": "AB1" }));
+
+        let (old, new) = snapshot_diff(after.as_object().unwrap(), after.as_object().unwrap());
+        assert!(old.is_empty() && new.is_empty());
+    }
+
+    fn new_order(client: &str, phone: &str) -> NewOrderInput {
+        NewOrderInput {
+            client_name: client.into(),
+            article_name: "Mug".into(),
+            items: vec![],
+            phone: phone.into(),
+            city: "Rabat".into(),
+            address: "1 Rue Test".into(),
+            delivery_company: "Amana".into(),
+            delivery_date: "2026-10-20".into(),
+            description: None,
+            delivery_fee: None,
+            discount: None,
+        }
+    }
+
+    fn status_events(conn: &Connection, id: i64) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare("SELECT old_value, new_value FROM order_events WHERE order_id = ?1 AND kind = 'status' ORDER BY id")
+            .unwrap();
+        let rows = stmt.query_map([id], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+        rows.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn done_checkbox_ticks_and_unticks_a_new_order() {
+        let conn = crate::db::open_test_db();
+        let id = insert_order(&conn, &new_order("Sara", "0612345678")).unwrap();
+
+        assert!(toggle_done(&conn, id, true).unwrap());
+        assert_eq!(current_status(&conn, id).unwrap(), OrderStatus::Delivered);
+        let (done, completed): (bool, Option<String>) = conn
+            .query_row("SELECT done, completed_at FROM orders WHERE id = ?1", [id], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert!(done && completed.is_some());
+        // Ticking again changes nothing
+        assert!(!toggle_done(&conn, id, true).unwrap());
+
+        assert!(toggle_done(&conn, id, false).unwrap());
+        assert_eq!(current_status(&conn, id).unwrap(), OrderStatus::Pending);
+        let done: bool = conn.query_row("SELECT done FROM orders WHERE id = ?1", [id], |r| r.get(0)).unwrap();
+        assert!(!done);
+
+        let steps: Vec<(String, String)> = [
+            ("pending", "confirmed"),
+            ("confirmed", "with_courier"),
+            ("with_courier", "delivered"),
+            ("delivered", "pending"),
+        ]
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+        assert_eq!(status_events(&conn, id), steps);
+    }
+
+    #[test]
+    fn taken_back_deliveries_do_not_count_as_delivered() {
+        let conn = crate::db::open_test_db();
+        let id = insert_order(&conn, &new_order("Sara", "0612345678")).unwrap();
+        let cte = status_reached_cte("delivered", "delivered_at", OrderStatus::Delivered);
+        let delivered = || -> i64 {
+            conn.query_row(&format!("WITH {cte} SELECT COUNT(*) FROM delivered"), [], |r| r.get(0)).unwrap()
+        };
+
+        toggle_done(&conn, id, true).unwrap();
+        assert_eq!(delivered(), 1);
+        toggle_done(&conn, id, false).unwrap();
+        assert_eq!(delivered(), 0);
+        toggle_done(&conn, id, true).unwrap();
+        assert_eq!(delivered(), 1);
+        // A return after delivery is a move along the lifecycle: still delivered once
+        change_status(&conn, id, OrderStatus::Returned).unwrap();
+        assert_eq!(delivered(), 1);
+    }
+
+    #[test]
+    fn done_checkbox_refuses_cancelled_orders() {
+        let conn = crate::db::open_test_db();
+        let id = insert_order(&conn, &new_order("Sara", "0612345678")).unwrap();
+        assert!(change_status(&conn, id, OrderStatus::Cancelled).unwrap());
+        assert_eq!(
+            toggle_done(&conn, id, true).unwrap_err(),
+            "Cannot move an order from 'cancelled' to 'delivered'"
+        );
+        assert_eq!(toggle_done(&conn, 999, true).unwrap_err(), "Order 999 not found");
+    }
+
+    fn filter_sql(filter: &OrderFilter) -> Result<(String, Vec<Value>), String> {
+        let conn = Connection::open_in_memory().unwrap();
+        order_filter_sql(&conn, filter)
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert_eq!(filter_sql(&OrderFilter::default()).unwrap(), ("1 = 1".to_string(), vec![]));
+    }
+
+    #[test]
+    fn filter_binds_every_value() {
+        let filter = OrderFilter {
+            status: vec![OrderStatus::Pending, OrderStatus::Confirmed],
+            delivery_company_id: Some(3),
+            city: Some(" Rabat ".into()),
+            delivery_to: Some("2026-10-31".into()),
+            text: Some("50%".into()),
+            ..Default::default()
+        };
+        let (sql, args) = filter_sql(&filter).unwrap();
+        assert!(sql.starts_with(
+            "1 = 1 AND o.status IN (?, ?) AND o.delivery_company_id = ? AND o.city = ? COLLATE NOCASE \
+             AND date(o.delivery_date) <= date(?) AND ("
+        ));
+        assert_eq!(sql.matches('?').count(), args.len());
+        assert_eq!(args[..5], [
+            Value::Text("pending".into()),
+            Value::Text("confirmed".into()),
+            Value::Integer(3),
+            Value::Text("Rabat".into()),
+            Value::Text("2026-10-31".into()),
+        ]);
+        assert_eq!(args[5], Value::Text(r"%50\%%".into()));
+    }
+
+    #[test]
+    fn filter_rejects_invalid_dates_and_ignores_blank_ones() {
+        let bad = OrderFilter { created_from: Some("next week".into()), ..Default::default() };
+        assert_eq!(filter_sql(&bad).unwrap_err(), "Invalid date 'next week'");
+        let blank = OrderFilter { created_from: Some("  ".into()), city: Some("".into()), ..Default::default() };
+        assert_eq!(filter_sql(&blank).unwrap().0, "1 = 1");
+    }
+
+    #[test]
+    fn relative_day_filters_bind_signed_offsets() {
+        let filter = OrderFilter {
+            delivery_from_days: Some(-7),
+            delivery_to_days: Some(0),
+            min_age_days: Some(3),
+            ..Default::default()
+        };
+        let (sql, args) = filter_sql(&filter).unwrap();
+        assert_eq!(sql.matches("date('now', 'localtime', ? || ' days')").count(), 3);
+        // "at least 3 days old" looks 3 days back
+        assert_eq!(args, [Value::Integer(-7), Value::Integer(0), Value::Integer(-3)]);
+    }
+
+    #[test]
+    fn relative_day_filters_are_bounded() {
+        let negative_age = OrderFilter { min_age_days: Some(-1), ..Default::default() };
+        assert_eq!(filter_sql(&negative_age).unwrap_err(), "Minimum age cannot be negative");
+        let far = OrderFilter { delivery_to_days: Some(-(MAX_RELATIVE_DAYS + 1)), ..Default::default() };
+        assert!(filter_sql(&far).unwrap_err().starts_with("Relative day filters must be within"));
+        let edge = OrderFilter { delivery_to_days: Some(MAX_RELATIVE_DAYS), ..Default::default() };
+        assert!(filter_sql(&edge).is_ok());
+    }
+
+    #[test]
+    fn overdue_implies_open() {
+        let open = OrderFilter { open_only: true, ..Default::default() };
+        let overdue = OrderFilter { overdue: true, ..Default::default() };
+        let both = OrderFilter { open_only: true, overdue: true, ..Default::default() };
+        let (open_sql, _) = filter_sql(&open).unwrap();
+        let (overdue_sql, _) = filter_sql(&overdue).unwrap();
+        assert_eq!(open_sql, "1 = 1 AND o.status IN ('pending','confirmed','with_courier')");
+        assert_eq!(overdue_sql, format!("{open_sql} AND date(o.delivery_date) < date('now','localtime')"));
+        assert_eq!(filter_sql(&both).unwrap().0, overdue_sql);
+    }
+}
@@ -0,0 +1,345 @@
+// src/commands/returns.rs
+// Goods coming back from a client. A return closes the order as "returned"
+// when everything came back; an exchange keeps the sale and ships the
+// replacement as a new order linked to the original one.
+use crate::app_state::AppState;
+use crate::commands::inventory::restock_returned;
+use crate::commands::orders::{apply_status, current_status, insert_order, load_order_items};
+use crate::db::{ensure_schema, open_db, record_order_event};
+use crate::models::orders::{NewOrderInput, OrderStatus, StatusError};
+use crate::models::returns::{
+    ExchangeInput, ExchangeResult, ReturnInput, ReturnItem, ReturnRecord,
+};
+use rusqlite::{params, Connection, TransactionBehavior};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn non_empty(v: Option<&str>) -> Option<String> {
+    v.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+// What can still come back of one order item
+struct Returnable {
+    article_id: Option<i64>,
+    article_name: String,
+    left: i64,
+}
+
+// Keyed by order item id
+fn returnable_items(conn: &Connection, order_id: i64) -> Result<HashMap<i64, Returnable>, String> {
+    let mut returned: HashMap<i64, i64> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT ri.order_item_id, SUM(ri.quantity)
+                FROM return_items ri
+                JOIN returns r ON r.id = ri.return_id
+                WHERE r.order_id = ?1 AND ri.order_item_id IS NOT NULL
+                GROUP BY ri.order_item_id
+                "#,
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([order_id], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+        for r in rows {
+            let (item_id, qty) = r.map_err(|e| e.to_string())?;
+            returned.insert(item_id, qty);
+        }
+    }
+
+    let mut out = HashMap::new();
+    for it in load_order_items(conn, order_id).map_err(|e| e.to_string())? {
+        let left = it.quantity - returned.get(&it.id).copied().unwrap_or(0);
+        out.insert(
+            it.id,
+            Returnable { article_id: it.article_id, article_name: it.article_name, left: left.max(0) },
+        );
+    }
+    Ok(out)
+}
+
+// Record the return/exchange with its items and optional restock.
+// Returns (return id, whether nothing is left to return on the order).
+fn record_return(
+    conn: &Connection,
+    order_id: i64,
+    kind: &str,
+    input: &ReturnInput,
+) -> Result<(i64, bool), String> {
+    let mut returnable = returnable_items(conn, order_id)?;
+
+    let requested: Vec<(i64, i64)> = if input.items.is_empty() {
+        let mut all: Vec<(i64, i64)> = returnable
+            .iter()
+            .filter(|(_, r)| r.left > 0)
+            .map(|(id, r)| (*id, r.left))
+            .collect();
+        all.sort_unstable();
+        all
+    } else {
+        input.items.iter().map(|it| (it.order_item_id, it.quantity)).collect()
+    };
+    if requested.is_empty() {
+        return Err(format!("Order {order_id} has nothing left to return"));
+    }
+
+    conn.execute(
+        r#"
+        INSERT INTO returns (order_id, kind, reason, restock, refund_amount, note)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+        params![
+            order_id,
+            kind,
+            non_empty(input.reason.as_deref()),
+            input.restock,
+            input.refund_amount.unwrap_or(0),
+            non_empty(input.note.as_deref())
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let return_id = conn.last_insert_rowid();
+
+    let mut summary = Vec::new();
+    for (item_id, qty) in requested {
+        let Some(item) = returnable.get_mut(&item_id) else {
+            return Err(format!("Item {item_id} does not belong to order {order_id}"));
+        };
+        let name = item.article_name.as_str();
+        if qty < 1 {
+            return Err(format!("{name}: returned quantity must be at least 1"));
+        }
+        if qty > item.left {
+            return Err(format!("{name}: only {} left to return", item.left));
+        }
+        item.left -= qty;
+
+        conn.execute(
+            r#"
+            INSERT INTO return_items (return_id, order_item_id, article_id, article_name, quantity)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![return_id, item_id, item.article_id, name, qty],
+        )
+        .map_err(|e| e.to_string())?;
+        if let (true, Some(aid)) = (input.restock, item.article_id) {
+            restock_returned(conn, aid, qty, order_id, &format!("{kind} #{return_id}"))
+                .map_err(|e| e.to_string())?;
+        }
+        summary.push(json!({ "articleName": name, "quantity": qty }));
+    }
+
+    record_order_event(
+        conn,
+        order_id,
+        kind,
+        None,
+        Some(&json!({ "returnId": return_id, "items": summary, "restock": input.restock }).to_string()),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let fully_returned = returnable.values().all(|r| r.left == 0);
+    Ok((return_id, fully_returned))
+}
+
+/// Record goods coming back. Returning everything moves the order to "returned";
+/// a partial return leaves the status alone.
+#[tauri::command]
+pub fn create_return(
+    state: tauri::State<AppState>,
+    order_id: i64,
+    input: ReturnInput,
+) -> Result<i64, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let refund = input.refund_amount.unwrap_or(0);
+    if refund < 0 {
+        return Err("Refund amount cannot be negative".to_string());
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let status = current_status(&tx, order_id)?;
+    if !matches!(status, OrderStatus::WithCourier | OrderStatus::Delivered | OrderStatus::Returned) {
+        return Err(format!(
+            "Only shipped orders can be returned (order {order_id} is {})",
+            status.as_str()
+        ));
+    }
+    // Earlier returns of the same order already gave part of the money back
+    let (collected, refunded): (i64, i64) = tx
+        .query_row(
+            r#"
+            SELECT o.amount_to_collect,
+                   COALESCE((SELECT SUM(r.refund_amount) FROM returns r WHERE r.order_id = o.id), 0)
+            FROM orders o
+            WHERE o.id = ?1
+            "#,
+            [order_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let refundable = (collected - refunded).max(0);
+    if refund > refundable {
+        return Err(format!(
+            "Refund ({refund}) exceeds what is left to refund on the order ({refundable} of {collected})"
+        ));
+    }
+
+    let (return_id, fully_returned) = record_return(&tx, order_id, "return", &input)?;
+
+    if fully_returned && status != OrderStatus::Returned {
+        if !status.can_transition_to(OrderStatus::Returned) {
+            return Err(StatusError::IllegalTransition { from: status, to: OrderStatus::Returned }
+                .to_string());
+        }
+        apply_status(&tx, order_id, status, OrderStatus::Returned).map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(return_id)
+}
+
+/// Swap delivered items: records what came back and creates the replacement order
+/// for the same client and courier. The original sale stays delivered.
+#[tauri::command]
+pub fn create_exchange(
+    state: tauri::State<AppState>,
+    order_id: i64,
+    input: ExchangeInput,
+) -> Result<ExchangeResult, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    if input.replacement_items.is_empty() {
+        return Err("An exchange needs at least one replacement item".to_string());
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let status = current_status(&tx, order_id)?;
+    if status != OrderStatus::Delivered {
+        return Err(format!(
+            "Only delivered orders can be exchanged (order {order_id} is {})",
+            status.as_str()
+        ));
+    }
+
+    let returned = ReturnInput {
+        reason: input.reason,
+        items: input.items,
+        restock: input.restock,
+        refund_amount: None, // the client pays nothing back or extra here
+        note: input.note,
+    };
+    let (return_id, _) = record_return(&tx, order_id, "exchange", &returned)?;
+
+    let replacement = tx
+        .query_row(
+            r#"
+            SELECT client_name, COALESCE(phone_raw, phone), city, address, delivery_company
+            FROM orders
+            WHERE id = ?1
+            "#,
+            [order_id],
+            |r| {
+                Ok(NewOrderInput {
+                    client_name: r.get(0)?,
+                    article_name: String::new(),
+                    items: input.replacement_items,
+                    phone: r.get(1)?,
+                    city: r.get(2)?,
+                    address: r.get(3)?,
+                    delivery_company: r.get(4)?,
+                    delivery_date: input.delivery_date,
+                    description: Some(format!("Exchange for order #{order_id}")),
+                    delivery_fee: input.delivery_fee,
+                    discount: input.discount,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let replacement_order_id = insert_order(&tx, &replacement)?;
+
+    tx.execute(
+        r#"UPDATE returns SET replacement_order_id = ?1 WHERE id = ?2"#,
+        params![replacement_order_id, return_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(ExchangeResult { return_id, replacement_order_id })
+}
+
+#[tauri::command]
+pub fn list_returns(
+    state: tauri::State<AppState>,
+    order_id: Option<i64>,
+) -> Result<Vec<ReturnRecord>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, order_id, kind, reason, restock, refund_amount,
+                   replacement_order_id, note, created_at
+            FROM returns
+            WHERE ?1 IS NULL OR order_id = ?1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![order_id], |row| {
+            Ok(ReturnRecord {
+                id: row.get(0)?,
+                order_id: row.get(1)?,
+                kind: row.get(2)?,
+                reason: row.get(3)?,
+                restock: row.get(4)?,
+                refund_amount: row.get(5)?,
+                replacement_order_id: row.get(6)?,
+                note: row.get(7)?,
+                created_at: row.get(8)?,
+                items: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+
+    let mut item_stmt = conn
+        .prepare(
+            r#"
+            SELECT id, order_item_id, article_id, article_name, quantity
+            FROM return_items
+            WHERE return_id = ?1
+            ORDER BY id ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    for rec in out.iter_mut() {
+        let rows = item_stmt
+            .query_map([rec.id], |row| {
+                Ok(ReturnItem {
+                    id: row.get(0)?,
+                    order_item_id: row.get(1)?,
+                    article_id: row.get(2)?,
+                    article_name: row.get(3)?,
+                    quantity: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows { rec.items.push(r.map_err(|e| e.to_string())?); }
+    }
+    Ok(out)
+}
@@ -1,8 +1,190 @@
 // src/commands/search.rs
 use crate::app_state::AppState;
+use crate::commands::orders::{order_list_item_from_row, order_page_where, ORDER_LIST_SELECT};
 use crate::db::{ensure_schema, open_db};
-use crate::util::escape_like;
-use rusqlite::{params, OptionalExtension};
+use crate::models::article::{ArticleNameHint, ArticleSuggestion};
+use crate::models::orders::{OrderListItem, OrderPage, OrderSearchHit, OrderSort, OrderStatus, SnippetPart};
+use crate::phone::phone_digits_sql;
+use crate::query::compile_query;
+use crate::util::{escape_like, fold_text};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashSet;
+
+// ---- Article autocomplete ----
+// Candidates are scored in Rust: the catalog plus names seen on orders stays small
+// enough, and SQLite has no accent folding or edit distance.
+
+// Below this similarity a misspelling is taken as a different word
+const MIN_FUZZY_SIMILARITY: f64 = 0.55;
+// A new name this close to an existing one triggers "did you mean"
+const DID_YOU_MEAN_SIMILARITY: f64 = 0.8;
+
+struct ArticleCandidate {
+    name: String,
+    article_id: Option<i64>,
+    sku: Option<String>,
+    freq: i64,
+    age_days: Option<f64>, // since it was last ordered
+}
+
+// Active catalog entries, then free-text names that are not in the catalog
+fn load_article_candidates(conn: &Connection) -> Result<Vec<ArticleCandidate>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, name, sku, freq, julianday('now') - julianday(last_used) FROM (
+          SELECT a.id AS id, a.name AS name, a.sku AS sku,
+                 (SELECT COUNT(*) FROM order_items i
+                    JOIN active_orders o ON o.id = i.order_id
+                  WHERE i.article_id = a.id) AS freq,
+                 (SELECT MAX(o.created_at) FROM order_items i
+                    JOIN active_orders o ON o.id = i.order_id
+                  WHERE i.article_id = a.id) AS last_used
+          FROM articles a
+          WHERE a.active = 1
+          UNION ALL
+          SELECT NULL, i.article_name, NULL, COUNT(*), MAX(o.created_at)
+          FROM order_items i
+          JOIN active_orders o ON o.id = i.order_id
+          WHERE NOT EXISTS (
+            SELECT 1 FROM articles a WHERE a.name = i.article_name COLLATE NOCASE
+          )
+          GROUP BY i.article_name
+        )
+        "#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ArticleCandidate {
+            article_id: row.get(0)?,
+            name: row.get(1)?,
+            sku: row.get(2)?,
+            freq: row.get(3)?,
+            age_days: row.get(4)?,
+        })
+    })?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r?); }
+    Ok(out)
+}
+
+// Comparison key: folded, letters and digits only ("T-Shirt" -> "tshirt")
+fn compact_key(text: &str) -> String {
+    fold_text(text).chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+fn trigrams(key: &str) -> HashSet<(char, char, char)> {
+    let padded: Vec<char> = format!("  {key} ").chars().collect();
+    padded.windows(3).map(|w| (w[0], w[1], w[2])).collect()
+}
+
+// Dice coefficient over character trigrams
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (ta, tb) = (trigrams(a), trigrams(b));
+    let shared = ta.intersection(&tb).count();
+    2.0 * shared as f64 / (ta.len() + tb.len()).max(1) as f64
+}
+
+// Edits (insert, delete, substitute, swap two neighbours) to turn `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() { row[0] = i; }
+    for (j, cell) in d[0].iter_mut().enumerate() { *cell = j; }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+fn edit_similarity(a: &[char], b: &[char]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+fn similarity(a: &str, b: &str) -> f64 {
+    let (ca, cb): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    edit_similarity(&ca, &cb).max(trigram_similarity(a, b))
+}
+
+// How well a compact query matches a text, 0..1; None when unrelated.
+// Exact > prefix > word prefix > substring > close misspelling.
+fn text_score(query: &str, text: &str) -> Option<f64> {
+    let key = compact_key(text);
+    if key.is_empty() {
+        return None;
+    }
+    if key == query {
+        return Some(1.0);
+    }
+    if key.starts_with(query) {
+        return Some(0.9);
+    }
+    let folded = fold_text(text);
+    if folded.split(|c: char| !c.is_alphanumeric()).any(|w| !w.is_empty() && w.starts_with(query)) {
+        return Some(0.85);
+    }
+    if key.contains(query) {
+        return Some(0.75);
+    }
+    if query.chars().count() < 3 {
+        return None;
+    }
+
+    // Compare with the whole name, and with its beginning while the user is still typing
+    let q: Vec<char> = query.chars().collect();
+    let k: Vec<char> = key.chars().collect();
+    let mut sim = similarity(query, &key);
+    if q.len() >= 4 && k.len() > q.len() {
+        sim = sim.max(edit_similarity(&q, &k[..q.len()]));
+    }
+    (sim >= MIN_FUZZY_SIMILARITY).then_some(0.7 * sim)
+}
+
+// Scored suggestions; an empty query lists the most used articles
+fn rank_articles(query: &str, candidates: Vec<ArticleCandidate>, limit: usize) -> Vec<ArticleSuggestion> {
+    let q = compact_key(query);
+    let max_freq = candidates.iter().map(|c| c.freq).max().unwrap_or(0);
+
+    let mut out: Vec<ArticleSuggestion> = candidates
+        .into_iter()
+        .filter_map(|c| {
+            let text = if q.is_empty() {
+                1.0
+            } else {
+                let by_name = text_score(&q, &c.name);
+                let by_sku = c.sku.as_deref().and_then(|s| text_score(&q, s));
+                by_name.into_iter().chain(by_sku).reduce(f64::max)?
+            };
+            let popularity = if max_freq > 0 {
+                (c.freq as f64).ln_1p() / (max_freq as f64).ln_1p()
+            } else {
+                0.0
+            };
+            let recency = c.age_days.map_or(0.0, |d| 1.0 / (1.0 + d.max(0.0) / 30.0));
+            let catalog = if c.article_id.is_some() { 1.0 } else { 0.0 };
+            let score = 0.8 * text + 0.1 * popularity + 0.05 * recency + 0.05 * catalog;
+            Some(ArticleSuggestion {
+                name: c.name,
+                article_id: c.article_id,
+                sku: c.sku,
+                score: (score * 1000.0).round() / 1000.0,
+                times_ordered: c.freq,
+            })
+        })
+        .collect();
+
+    out.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    out.truncate(limit);
+    out
+}
 
 #[tauri::command]
 pub fn search_article_names(
@@ -10,34 +192,67 @@ pub fn search_article_names(
     query: String,
     limit: Option<i64>,
 ) -> Result<Vec<String>, String> {
+    let suggestions = suggest_articles(state, query, limit)?;
+    Ok(suggestions.into_iter().map(|s| s.name).collect())
+}
+
+/// Typo-tolerant article autocomplete over the catalog (name or SKU) and names
+/// used on orders, ranked by match quality, then popularity and recency.
+#[tauri::command]
+pub fn suggest_articles(
+    state: tauri::State<AppState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<ArticleSuggestion>, String> {
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
-    // contains search: %query% (case-insensitive in SQLite for ASCII)
-    let pat = format!("%{}%", escape_like(&query));
-    let lim = limit.unwrap_or(10).max(1);
+    let lim = limit.unwrap_or(10).max(1) as usize;
+    let candidates = load_article_candidates(&conn).map_err(|e| e.to_string())?;
+    Ok(rank_articles(&query, candidates, lim))
+}
 
-    // Order by frequency, then most recent first
-    let mut stmt = conn
-        .prepare(
-            r#"
-            SELECT o.article_name
-            FROM orders o
-            WHERE o.article_name LIKE ?1 ESCAPE '\\'
-            GROUP BY o.article_name
-            ORDER BY COUNT(*) DESC, MAX(o.created_at) DESC
-            LIMIT ?2
-            "#,
-        )
-        .map_err(|e| e.to_string())?;
+/// For a name typed on an order: the existing article it is suspiciously close to
+/// ("tshirt" vs "T-Shirt", "Tshrit" vs "T-Shirt"), or None when it exists as typed
+/// or nothing is close.
+#[tauri::command]
+pub fn check_article_name(
+    state: tauri::State<AppState>,
+    name: String,
+) -> Result<Option<ArticleNameHint>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
 
-    let rows = stmt
-        .query_map(params![pat, lim], |row| row.get::<_, String>(0))
-        .map_err(|e| e.to_string())?;
+    let key = compact_key(&name);
+    if key.chars().count() < 3 {
+        return Ok(None);
+    }
+    let candidates = load_article_candidates(&conn).map_err(|e| e.to_string())?;
+    let typed = fold_text(name.trim());
+    if candidates.iter().any(|c| fold_text(c.name.trim()) == typed) {
+        return Ok(None);
+    }
 
-    let mut out = Vec::new();
-    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
-    Ok(out)
+    let best = candidates
+        .into_iter()
+        .map(|c| {
+            let other = compact_key(&c.name);
+            let sim = if other == key { 1.0 } else { similarity(&key, &other) };
+            (sim, c)
+        })
+        .filter(|(sim, _)| *sim >= DID_YOU_MEAN_SIMILARITY)
+        // Closest first; on a tie prefer the catalog, then the most used
+        .max_by(|(sa, a), (sb, b)| {
+            sa.total_cmp(sb)
+                .then_with(|| a.article_id.is_some().cmp(&b.article_id.is_some()))
+                .then_with(|| a.freq.cmp(&b.freq))
+        });
+
+    Ok(best.map(|(sim, c)| ArticleNameHint {
+        suggestion: c.name,
+        article_id: c.article_id,
+        similarity: (sim * 1000.0).round() / 1000.0,
+    }))
 }
 
 #[tauri::command]
@@ -48,15 +263,37 @@ pub fn get_latest_description_for_article(
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
+    // Catalog default first, then the most recent order that used this article
+    let catalog: Option<String> = conn
+        .query_row(
+            r#"
+            SELECT default_description
+            FROM articles
+            WHERE name = ?1 COLLATE NOCASE
+              AND default_description IS NOT NULL
+              AND TRIM(default_description) <> ''
+            "#,
+            params![name],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if catalog.is_some() {
+        return Ok(catalog);
+    }
+
     let mut stmt = conn
         .prepare(
             r#"
-            SELECT description
-            FROM orders
-            WHERE article_name = ?1
-              AND description IS NOT NULL
-              AND TRIM(description) <> ''
-            ORDER BY created_at DESC, id DESC
+            SELECT o.description
+            FROM active_orders o
+            WHERE EXISTS (
+                    SELECT 1 FROM order_items i
+                    WHERE i.order_id = o.id AND i.article_name = ?1
+                  )
+              AND o.description IS NOT NULL
+              AND TRIM(o.description) <> ''
+            ORDER BY o.created_at DESC, o.id DESC
             LIMIT 1
             "#,
         )
@@ -69,3 +306,291 @@ pub fn get_latest_description_for_article(
 
     Ok(desc)
 }
+
+/// Orders whose reference contains the query (case-insensitive); exact matches first.
+#[tauri::command]
+pub fn search_order_references(
+    state: tauri::State<AppState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<OrderListItem>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let q = query.trim();
+    if q.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pat = format!("%{}%", escape_like(q));
+    let lim = limit.unwrap_or(10).max(1);
+
+    let sql = format!(
+        r#"{ORDER_LIST_SELECT}
+           FROM active_orders o
+           WHERE o.reference LIKE ?1 ESCAPE '\'
+           ORDER BY (o.reference = ?2 COLLATE NOCASE) DESC, o.created_at DESC, o.id DESC
+           LIMIT ?3"#
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![pat, q, lim], order_list_item_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+// Highlight markers inside FTS snippets; private-use characters nobody types
+const HIT_START: char = '\u{E000}';
+const HIT_END: char = '\u{E001}';
+
+// User text to an FTS5 query: every word must match as a prefix ("moha" finds
+// "Mohamed"). Words are quoted so FTS operators in the input are taken literally.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{w}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut out = Vec::new();
+    let mut matched = false;
+    for (i, piece) in snippet.split([HIT_START, HIT_END]).enumerate() {
+        if !piece.is_empty() {
+            out.push(SnippetPart { text: piece.to_string(), matched });
+        }
+        // Markers alternate start/end; anything after a start marker is a hit
+        matched = i % 2 == 0;
+    }
+    out
+}
+
+/// Ranked full-text search over orders outside the trash: reference, client,
+/// phone, address, city, description, articles and tracking number.
+/// A query made only of digits also finds phone numbers containing them.
+#[tauri::command]
+pub fn search_orders(
+    state: tauri::State<AppState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<OrderSearchHit>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let lim = limit.unwrap_or(20).clamp(1, 200);
+    let mut out: Vec<OrderSearchHit> = Vec::new();
+
+    if let Some(fts) = fts_query(&query) {
+        // bm25 weights follow the column order of orders_fts (db.rs)
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT o.id, COALESCE(o.reference, ''), o.client_name, o.city, o.delivery_date, o.status,
+                       snippet(orders_fts, -1, ?2, ?3, '…', 12)
+                FROM orders_fts
+                JOIN active_orders o ON o.id = orders_fts.rowid
+                WHERE orders_fts MATCH ?1
+                ORDER BY bm25(orders_fts, 10.0, 5.0, 3.0, 3.0, 1.0, 2.0, 1.0, 1.0, 4.0), o.id DESC
+                LIMIT ?4
+                "#,
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![fts, HIT_START.to_string(), HIT_END.to_string(), lim], |row| {
+                Ok(OrderSearchHit {
+                    id: row.get(0)?,
+                    reference: row.get(1)?,
+                    client_name: row.get(2)?,
+                    city: row.get(3)?,
+                    delivery_date: row.get(4)?,
+                    status: OrderStatus::parse(&row.get::<_, String>(5)?).unwrap_or_default(),
+                    snippet: snippet_parts(&row.get::<_, String>(6)?),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    }
+
+    // Phone fragments sit in the middle of a number, where prefixes can't reach
+    let digits: String = query.chars().filter(char::is_ascii_digit).collect();
+    let phone_like = query.chars().all(|c| c.is_ascii_digit() || " +-.()".contains(c));
+    if phone_like && digits.len() >= 4 && (out.len() as i64) < lim {
+        let sql = format!(
+            "SELECT o.id, COALESCE(o.reference, ''), o.client_name, o.city, o.delivery_date, o.status,
+                    COALESCE(o.phone_raw, o.phone)
+             FROM active_orders o
+             WHERE {} LIKE ?1 OR {} LIKE ?1
+             ORDER BY o.created_at DESC, o.id DESC
+             LIMIT ?2",
+            phone_digits_sql("o.phone"),
+            phone_digits_sql("o.phone_raw")
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![format!("%{digits}%"), lim], |row| {
+                Ok(OrderSearchHit {
+                    id: row.get(0)?,
+                    reference: row.get(1)?,
+                    client_name: row.get(2)?,
+                    city: row.get(3)?,
+                    delivery_date: row.get(4)?,
+                    status: OrderStatus::parse(&row.get::<_, String>(5)?).unwrap_or_default(),
+                    snippet: vec![SnippetPart { text: row.get(6)?, matched: true }],
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows {
+            let hit = r.map_err(|e| e.to_string())?;
+            if (out.len() as i64) < lim && !out.iter().any(|h| h.id == hit.id) {
+                out.push(hit);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Orders matching a query-language expression (see `query.rs`), paged like
+/// `query_orders`. Syntax errors name the character position at fault.
+#[tauri::command]
+pub fn search_orders_dsl(
+    state: tauri::State<AppState>,
+    query: String,
+    sort: Option<OrderSort>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<OrderPage, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (where_sql, args) = compile_query(&query).map_err(|e| e.to_string())?;
+    order_page_where(&conn, &where_sql, args, sort.unwrap_or_default(), cursor, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    fn candidate(name: &str, article_id: Option<i64>, freq: i64) -> ArticleCandidate {
+        ArticleCandidate { name: name.into(), article_id, sku: None, freq, age_days: None }
+    }
+
+    #[test]
+    fn edit_distance_counts_a_transposition_as_one_edit() {
+        assert_eq!(edit_distance(&chars("mug"), &chars("mgu")), 1);
+        assert_eq!(edit_distance(&chars("tshirt"), &chars("tshrit")), 1);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars("mug"), &chars("mug")), 0);
+        // Optimal string alignment: a swapped pair is not edited again
+        assert_eq!(edit_distance(&chars("ca"), &chars("abc")), 3);
+    }
+
+    #[test]
+    fn edit_distance_handles_empty_strings() {
+        assert_eq!(edit_distance(&[], &[]), 0);
+        assert_eq!(edit_distance(&[], &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("abc"), &[]), 3);
+        assert_eq!(edit_similarity(&[], &[]), 1.0);
+        assert_eq!(edit_similarity(&chars("abc"), &[]), 0.0);
+    }
+
+    #[test]
+    fn trigram_dice_coefficient() {
+        assert_eq!(trigram_similarity("tshirt", "tshirt"), 1.0);
+        // "  a", " ab" shared out of four trigrams each
+        assert_eq!(trigram_similarity("abc", "abd"), 0.5);
+        assert_eq!(trigram_similarity("abc", "xyz"), 0.0);
+        assert_eq!(trigram_similarity("", "abc"), 0.0);
+        assert_eq!(trigram_similarity("", ""), 1.0);
+    }
+
+    #[test]
+    fn comparison_key_ignores_accents_case_and_punctuation() {
+        assert_eq!(compact_key("Été T-Shirt"), "etetshirt");
+        assert_eq!(compact_key("  "), "");
+        assert_eq!(text_score("tshirt", "T-SHIRT"), Some(1.0));
+        assert_eq!(text_score(&compact_key("Crème"), "creme"), Some(1.0));
+    }
+
+    #[test]
+    fn text_score_prefers_exact_then_prefix_then_word_then_substring() {
+        assert_eq!(text_score("mug", "Mug"), Some(1.0));
+        assert_eq!(text_score("mug", "Mug XL"), Some(0.9));
+        assert_eq!(text_score("shirt", "T-Shirt"), Some(0.85));
+        assert_eq!(text_score("hir", "T-Shirt"), Some(0.75));
+        let typo = text_score("tshrit", "T-Shirt").unwrap();
+        assert!(typo > 0.5 && typo < 0.75, "{typo}");
+        assert_eq!(text_score("xyz", "T-Shirt"), None);
+        // Too short to guess a misspelling
+        assert_eq!(text_score("zq", "Cab"), None);
+        assert_eq!(text_score("mug", "---"), None);
+    }
+
+    #[test]
+    fn ranking_puts_exact_matches_before_popular_prefixes_and_typos() {
+        let candidates = vec![
+            candidate("Mug XL", None, 10),
+            candidate("Tasse", None, 50),
+            candidate("Mgu", None, 0),
+            candidate("Mug", Some(1), 1),
+        ];
+        let names: Vec<String> = rank_articles("MUG", candidates, 10).into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["Mug", "Mug XL", "Mgu"]);
+    }
+
+    #[test]
+    fn empty_query_ranks_by_use_and_honours_the_limit() {
+        let candidates = vec![candidate("B", None, 1), candidate("A", None, 1), candidate("C", Some(3), 9)];
+        let names: Vec<String> = rank_articles(" ", candidates, 2).into_iter().map(|s| s.name).collect();
+        // Equal scores fall back to the name
+        assert_eq!(names, ["C", "A"]);
+    }
+
+    #[test]
+    fn ranking_matches_accented_names_and_skus() {
+        let mut with_sku = candidate("Casquette", Some(2), 0);
+        with_sku.sku = Some("CAP-01".into());
+        let found = rank_articles("ete", vec![candidate("Été", None, 0), with_sku], 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Été");
+
+        let mut with_sku = candidate("Casquette", Some(2), 0);
+        with_sku.sku = Some("CAP-01".into());
+        let found = rank_articles("cap01", vec![with_sku], 10);
+        assert_eq!(found[0].name, "Casquette");
+    }
+
+    #[test]
+    fn fts_query_quotes_words_as_prefixes() {
+        assert_eq!(fts_query("moha rabat").as_deref(), Some(r#""moha"* "rabat"*"#));
+        // FTS syntax in the input is split away rather than interpreted
+        assert_eq!(fts_query(r#"a" OR b*"#).as_deref(), Some(r#""a"* "OR"* "b"*"#));
+        assert_eq!(fts_query("Été-2026").as_deref(), Some(r#""Été"* "2026"*"#));
+        assert_eq!(fts_query(" -*\" "), None);
+    }
+
+    #[test]
+    fn snippet_parts_alternate_on_markers() {
+        let snippet = format!("…rue {HIT_START}Moha{HIT_END}med V, {HIT_START}Rabat{HIT_END}");
+        let parts: Vec<(String, bool)> = snippet_parts(&snippet).into_iter().map(|p| (p.text, p.matched)).collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("…rue ".to_string(), false),
+                ("Moha".to_string(), true),
+                ("med V, ".to_string(), false),
+                ("Rabat".to_string(), true),
+            ]
+        );
+        assert!(snippet_parts("").is_empty());
+        assert!(!snippet_parts("plain")[0].matched);
+    }
+}
@@ -1,6 +1,9 @@
 // src/commands/settings.rs
 use crate::app_state::AppState;
 use crate::db::{ensure_schema, open_db};
+use crate::money::is_valid_currency;
+use crate::phone::is_valid_country_code;
+use crate::reference::validate_reference_pattern;
 use rusqlite::OptionalExtension;
 
 #[tauri::command]
@@ -25,6 +28,37 @@ pub fn set_setting(state: tauri::State<AppState>, key: String, value: String) ->
     let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
     ensure_schema(&conn).map_err(|e| e.to_string())?;
 
+    let value = match key.as_str() {
+        "defaultCountryCode" => {
+            let cc = value.trim().trim_start_matches('+').to_string();
+            if !is_valid_country_code(&cc) {
+                return Err(format!("Invalid country code '{value}': expected 1 to 3 digits"));
+            }
+            cc
+        }
+        "maxFailedAttempts" => match value.trim().parse::<i64>() {
+            Ok(n) if n >= 0 => n.to_string(),
+            _ => return Err(format!("Invalid attempt limit '{value}': expected 0 or more (0 disables)")),
+        },
+        "undoDepth" => match value.trim().parse::<i64>() {
+            Ok(n) if n >= 0 => n.to_string(),
+            _ => return Err(format!("Invalid undo depth '{value}': expected 0 or more (0 disables)")),
+        },
+        "orderReferencePattern" => {
+            let pattern = value.trim().to_string();
+            validate_reference_pattern(&pattern)?;
+            pattern
+        }
+        "currency" => {
+            let code = value.trim().to_ascii_uppercase();
+            if !is_valid_currency(&code) {
+                return Err(format!("Invalid currency '{value}': expected a 3-letter code"));
+            }
+            code
+        }
+        _ => value,
+    };
+
     conn.execute(
         r#"
         INSERT INTO settings (key, value, updated_at)
@@ -0,0 +1,334 @@
+// src/commands/settlements.rs
+// Cash-on-delivery reconciliation: couriers hand over the cash they collected
+// in batches; each batch settles a set of delivered orders once and for all.
+use crate::app_state::AppState;
+use crate::db::{default_currency, ensure_schema, open_db};
+use crate::models::settlement::{
+    Settlement, SettlementDetail, SettlementInput, SettlementLine, UnsettledOrder,
+};
+use crate::money::is_valid_currency;
+use crate::util::csv_field;
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use std::collections::HashMap;
+
+// Delivered, not yet settled, for ?1 company, completed within [?2, ?3] (local dates), priced in ?4
+const UNSETTLED_WHERE: &str = r#"
+    o.status = 'delivered'
+    AND o.delivery_company_id = ?1
+    AND (?2 IS NULL OR date(o.completed_at, 'localtime') >= date(?2))
+    AND (?3 IS NULL OR date(o.completed_at, 'localtime') <= date(?3))
+    AND o.currency = ?4
+    AND NOT EXISTS (SELECT 1 FROM settlement_orders so WHERE so.order_id = o.id)
+"#;
+
+const SETTLEMENT_SELECT: &str = r#"
+    SELECT s.id, s.delivery_company_id, COALESCE(dc.name, ''),
+           s.date_from, s.date_to,
+           (SELECT COUNT(*) FROM settlement_orders so WHERE so.settlement_id = s.id),
+           s.expected_amount, s.received_amount,
+           (SELECT COUNT(*) FROM settlement_orders so
+             WHERE so.settlement_id = s.id AND so.received_amount <> so.expected_amount),
+           s.currency, s.note, s.created_at
+    FROM settlements s
+    LEFT JOIN delivery_companies dc ON dc.id = s.delivery_company_id
+"#;
+
+fn settlement_from_row(row: &Row) -> rusqlite::Result<Settlement> {
+    let expected_amount: i64 = row.get(6)?;
+    let received_amount: i64 = row.get(7)?;
+    Ok(Settlement {
+        id: row.get(0)?,
+        delivery_company_id: row.get(1)?,
+        delivery_company: row.get(2)?,
+        date_from: row.get(3)?,
+        date_to: row.get(4)?,
+        order_count: row.get(5)?,
+        expected_amount,
+        received_amount,
+        discrepancy: received_amount - expected_amount,
+        flagged_orders: row.get(8)?,
+        currency: row.get(9)?,
+        note: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+fn non_empty(v: Option<&str>) -> Option<String> {
+    v.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+// Requested currency, upper-cased; the default currency when none is given
+fn settlement_currency(conn: &Connection, currency: Option<&str>) -> Result<String, String> {
+    match non_empty(currency) {
+        None => default_currency(conn).map_err(|e| e.to_string()),
+        Some(c) if is_valid_currency(&c) => Ok(c.to_ascii_uppercase()),
+        Some(c) => Err(format!("Invalid currency '{c}'; use a 3-letter code like MAD")),
+    }
+}
+
+fn load_unsettled(
+    conn: &Connection,
+    company_id: i64,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+    currency: &str,
+) -> Result<Vec<UnsettledOrder>, rusqlite::Error> {
+    let sql = format!(
+        "SELECT o.id, o.client_name, o.article_name, o.city, o.completed_at,
+                o.amount_to_collect, o.currency
+         FROM active_orders o
+         WHERE {UNSETTLED_WHERE}
+         ORDER BY o.completed_at ASC, o.id ASC"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![company_id, date_from, date_to, currency], |row| {
+        Ok(UnsettledOrder {
+            order_id: row.get(0)?,
+            client_name: row.get(1)?,
+            article_name: row.get(2)?,
+            city: row.get(3)?,
+            completed_at: row.get(4)?,
+            amount_to_collect: row.get(5)?,
+            currency: row.get(6)?,
+        })
+    })?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r?); }
+    Ok(out)
+}
+
+fn load_settlement(conn: &Connection, id: i64) -> Result<SettlementDetail, String> {
+    let sql = format!("{SETTLEMENT_SELECT} WHERE s.id = ?1");
+    let settlement = conn
+        .query_row(&sql, [id], settlement_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Settlement {id} not found"))?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT so.order_id, o.client_name, o.completed_at,
+                   so.expected_amount, so.received_amount, so.note
+            FROM settlement_orders so
+            LEFT JOIN orders o ON o.id = so.order_id
+            WHERE so.settlement_id = ?1
+            ORDER BY o.completed_at ASC, so.order_id ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([id], |row| {
+            let expected_amount: i64 = row.get(3)?;
+            let received_amount: i64 = row.get(4)?;
+            Ok(SettlementLine {
+                order_id: row.get(0)?,
+                client_name: row.get(1)?,
+                completed_at: row.get(2)?,
+                expected_amount,
+                received_amount,
+                discrepancy: received_amount - expected_amount,
+                note: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = Vec::new();
+    for r in rows { lines.push(r.map_err(|e| e.to_string())?); }
+    Ok(SettlementDetail { settlement, lines })
+}
+
+/// Orders priced in `currency` (default: the currency setting); couriers settle each currency separately.
+#[tauri::command]
+pub fn list_unsettled_orders(
+    state: tauri::State<AppState>,
+    delivery_company_id: i64,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    currency: Option<String>,
+) -> Result<Vec<UnsettledOrder>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let currency = settlement_currency(&conn, currency.as_deref())?;
+    load_unsettled(
+        &conn,
+        delivery_company_id,
+        non_empty(date_from.as_deref()).as_deref(),
+        non_empty(date_to.as_deref()).as_deref(),
+        &currency,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Settle every unsettled delivered order of the company in the date range and currency.
+/// Orders without a line are taken as paid in full; lines record what was actually received.
+/// The received total must equal the sum over the orders, so a batch cannot disagree with itself.
+#[tauri::command]
+pub fn create_settlement(
+    state: tauri::State<AppState>,
+    settlement: SettlementInput,
+) -> Result<i64, String> {
+    let mut conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    if settlement.received_amount < 0 {
+        return Err("Received amount cannot be negative".to_string());
+    }
+    let date_from = non_empty(settlement.date_from.as_deref());
+    let date_to = non_empty(settlement.date_to.as_deref());
+
+    let mut received: HashMap<i64, (i64, Option<String>)> = HashMap::new();
+    for line in &settlement.lines {
+        if line.received_amount < 0 {
+            return Err(format!("Order {}: received amount cannot be negative", line.order_id));
+        }
+        received.insert(line.order_id, (line.received_amount, non_empty(line.note.as_deref())));
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+
+    let company_exists = tx
+        .query_row(
+            r#"SELECT EXISTS(SELECT 1 FROM delivery_companies WHERE id = ?1)"#,
+            [settlement.delivery_company_id],
+            |r| r.get::<_, bool>(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !company_exists {
+        return Err(format!("Delivery company {} not found", settlement.delivery_company_id));
+    }
+
+    let currency = settlement_currency(&tx, settlement.currency.as_deref())?;
+    let orders = load_unsettled(
+        &tx,
+        settlement.delivery_company_id,
+        date_from.as_deref(),
+        date_to.as_deref(),
+        &currency,
+    )
+    .map_err(|e| e.to_string())?;
+    if orders.is_empty() {
+        return Err("No delivered orders left to settle for this company and period".to_string());
+    }
+    if let Some(stray) = received.keys().find(|id| !orders.iter().any(|o| o.order_id == **id)) {
+        return Err(format!("Order {stray} is not an unsettled delivery of this company and period"));
+    }
+
+    let expected_amount: i64 = orders.iter().map(|o| o.amount_to_collect).sum();
+    let lines: Vec<(i64, i64, i64, Option<String>)> = orders
+        .iter()
+        .map(|o| {
+            let (got, note) = received.remove(&o.order_id).unwrap_or((o.amount_to_collect, None));
+            (o.order_id, o.amount_to_collect, got, note)
+        })
+        .collect();
+    let lines_total: i64 = lines.iter().map(|(_, _, got, _)| got).sum();
+    if lines_total != settlement.received_amount {
+        return Err(format!(
+            "Received amount ({}) does not match the per-order amounts ({lines_total}); add a line for each order paid differently",
+            settlement.received_amount
+        ));
+    }
+
+    tx.execute(
+        r#"
+        INSERT INTO settlements
+          (delivery_company_id, date_from, date_to, expected_amount, received_amount, currency, note)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        params![
+            settlement.delivery_company_id,
+            date_from,
+            date_to,
+            expected_amount,
+            settlement.received_amount,
+            currency,
+            non_empty(settlement.note.as_deref())
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+
+    {
+        let mut stmt = tx
+            .prepare(
+                r#"
+                INSERT INTO settlement_orders
+                  (settlement_id, order_id, expected_amount, received_amount, note)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .map_err(|e| e.to_string())?;
+        for (order_id, expected, got, note) in &lines {
+            stmt.execute(params![id, order_id, expected, got, note])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn list_settlements(
+    state: tauri::State<AppState>,
+    delivery_company_id: Option<i64>,
+) -> Result<Vec<Settlement>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let sql = format!(
+        "{SETTLEMENT_SELECT}
+         WHERE ?1 IS NULL OR s.delivery_company_id = ?1
+         ORDER BY s.created_at DESC, s.id DESC"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![delivery_company_id], settlement_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for r in rows { out.push(r.map_err(|e| e.to_string())?); }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn get_settlement(state: tauri::State<AppState>, id: i64) -> Result<SettlementDetail, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+    load_settlement(&conn, id)
+}
+
+/// One row per order, amounts in minor units; the frontend saves the text as a .csv file.
+#[tauri::command]
+pub fn export_settlement_csv(state: tauri::State<AppState>, id: i64) -> Result<String, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let detail = load_settlement(&conn, id)?;
+    let s = &detail.settlement;
+    let mut out = String::from(
+        "settlement_id,delivery_company,currency,order_id,client_name,completed_at,expected_amount,received_amount,discrepancy,note\n",
+    );
+    for l in &detail.lines {
+        let row = [
+            s.id.to_string(),
+            csv_field(&s.delivery_company),
+            s.currency.clone(),
+            l.order_id.to_string(),
+            csv_field(l.client_name.as_deref().unwrap_or("")),
+            l.completed_at.clone().unwrap_or_default(),
+            l.expected_amount.to_string(),
+            l.received_amount.to_string(),
+            l.discrepancy.to_string(),
+            csv_field(l.note.as_deref().unwrap_or("")),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    Ok(out)
+}
@@ -0,0 +1,163 @@
+// src/commands/views.rs
+use crate::app_state::AppState;
+use crate::commands::orders::{count_orders, order_filter_sql, order_page};
+use crate::db::{ensure_schema, open_db};
+use crate::models::orders::{OrderFilter, OrderPage, OrderSort};
+use crate::models::view::{SavedView, SavedViewInput};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const MAX_VIEW_NAME_LEN: usize = 60;
+
+// Trim the name and dry-run the filter so a bad definition fails on save, not on click
+fn clean_input(conn: &Connection, input: SavedViewInput) -> Result<(String, String, String), String> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err("View name is required".to_string());
+    }
+    if name.chars().count() > MAX_VIEW_NAME_LEN {
+        return Err(format!("View name must be at most {MAX_VIEW_NAME_LEN} characters"));
+    }
+    order_filter_sql(conn, &input.filter)?;
+    let filter = serde_json::to_string(&input.filter).map_err(|e| e.to_string())?;
+    let sort = serde_json::to_string(&input.sort.unwrap_or_default()).map_err(|e| e.to_string())?;
+    Ok((name, filter, sort))
+}
+
+// Friendlier than the raw UNIQUE constraint message
+fn ensure_name_free(conn: &Connection, name: &str, id: Option<i64>) -> Result<(), String> {
+    let taken: bool = conn
+        .query_row(
+            r#"SELECT EXISTS(SELECT 1 FROM saved_views WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2)"#,
+            params![name, id],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if taken {
+        return Err(format!("A view named '{name}' already exists"));
+    }
+    Ok(())
+}
+
+struct StoredView {
+    id: i64,
+    name: String,
+    filter: OrderFilter,
+    sort: OrderSort,
+    created_at: String,
+    updated_at: String,
+}
+
+fn load_view(conn: &Connection, id: i64) -> Result<StoredView, String> {
+    let row: Option<(String, String, String, String, String)> = conn
+        .query_row(
+            r#"SELECT name, filter, sort, created_at, updated_at FROM saved_views WHERE id = ?1"#,
+            [id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (name, filter, sort, created_at, updated_at) = row.ok_or_else(|| format!("View {id} not found"))?;
+    let filter = serde_json::from_str(&filter).map_err(|e| format!("View '{name}' has an invalid filter: {e}"))?;
+    let sort = serde_json::from_str(&sort).map_err(|e| format!("View '{name}' has an invalid sort: {e}"))?;
+    Ok(StoredView { id, name, filter, sort, created_at, updated_at })
+}
+
+/// All views with their current counts, alphabetically.
+#[tauri::command]
+pub fn list_saved_views(state: tauri::State<AppState>) -> Result<Vec<SavedView>, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(r#"SELECT id FROM saved_views ORDER BY name COLLATE NOCASE ASC"#)
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |r| r.get::<_, i64>(0)).map_err(|e| e.to_string())?;
+    let mut ids = Vec::new();
+    for r in rows { ids.push(r.map_err(|e| e.to_string())?); }
+
+    let mut out = Vec::new();
+    for id in ids {
+        let v = load_view(&conn, id)?;
+        // Day filters are relative, so the badge is recounted on every listing
+        let count = count_orders(&conn, &v.filter)?;
+        out.push(SavedView {
+            id: v.id,
+            name: v.name,
+            filter: v.filter,
+            sort: v.sort,
+            count,
+            created_at: v.created_at,
+            updated_at: v.updated_at,
+        });
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+pub fn create_saved_view(state: tauri::State<AppState>, view: SavedViewInput) -> Result<i64, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (name, filter, sort) = clean_input(&conn, view)?;
+    ensure_name_free(&conn, &name, None)?;
+    conn.execute(
+        r#"INSERT INTO saved_views (name, filter, sort) VALUES (?1, ?2, ?3)"#,
+        params![name, filter, sort],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub fn update_saved_view(
+    state: tauri::State<AppState>,
+    id: i64,
+    view: SavedViewInput,
+) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let (name, filter, sort) = clean_input(&conn, view)?;
+    ensure_name_free(&conn, &name, Some(id))?;
+    let changed = conn
+        .execute(
+            r#"
+            UPDATE saved_views SET
+              name = ?1,
+              filter = ?2,
+              sort = ?3,
+              updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE id = ?4
+            "#,
+            params![name, filter, sort, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("View {id} not found"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_saved_view(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+    conn.execute(r#"DELETE FROM saved_views WHERE id = ?1"#, [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Orders matching a saved view, paged like `query_orders`; `total` is the badge count.
+#[tauri::command]
+pub fn execute_view(
+    state: tauri::State<AppState>,
+    id: i64,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<OrderPage, String> {
+    let conn = open_db(&state.db_path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn).map_err(|e| e.to_string())?;
+
+    let view = load_view(&conn, id)?;
+    order_page(&conn, &view.filter, view.sort, cursor, limit)
+}
@@ -0,0 +1,239 @@
+// src/csv.rs
+// Reading CSV files exported by spreadsheets and marketplaces: byte decoding for
+// the encodings those tools actually produce, and RFC 4180 records (quoted
+// fields, doubled quotes, line breaks inside quotes, CRLF or LF).
+
+// Windows-1252 differs from Latin-1 only in 0x80..=0x9F; undefined bytes map to U+FFFD
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž', '\u{fffd}',
+    '\u{fffd}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{fffd}', 'ž', 'Ÿ',
+];
+
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> Result<String, String> {
+    let pairs = bytes.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err("File is not valid UTF-16 (odd number of bytes)".to_string());
+    }
+    let units: Vec<u16> = pairs
+        .map(|p| if big_endian { u16::from_be_bytes([p[0], p[1]]) } else { u16::from_le_bytes([p[0], p[1]]) })
+        .collect();
+    String::from_utf16(&units).map_err(|_| "File is not valid UTF-16".to_string())
+}
+
+/// Decode file bytes as `encoding`: utf-8 (default), utf-16, utf-16le, utf-16be,
+/// windows-1252 or latin1. A byte order mark wins over the requested encoding.
+pub fn decode_text(bytes: &[u8], encoding: Option<&str>) -> Result<String, String> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8(rest.to_vec()).map_err(|_| "File is not valid UTF-8".to_string());
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest, false);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return decode_utf16(rest, true);
+    }
+
+    let encoding = encoding.map(|e| e.trim().to_ascii_lowercase().replace('_', "-")).unwrap_or_default();
+    match encoding.as_str() {
+        "" | "utf-8" | "utf8" => String::from_utf8(bytes.to_vec())
+            .map_err(|_| "File is not valid UTF-8; try windows-1252 or latin1".to_string()),
+        "utf-16" | "utf-16le" => decode_utf16(bytes, false),
+        "utf-16be" => decode_utf16(bytes, true),
+        "windows-1252" | "cp1252" => Ok(bytes
+            .iter()
+            .map(|&b| match b {
+                0x80..=0x9F => CP1252_HIGH[(b - 0x80) as usize],
+                _ => b as char,
+            })
+            .collect()),
+        "latin1" | "latin-1" | "iso-8859-1" => Ok(bytes.iter().map(|&b| b as char).collect()),
+        other => Err(format!("Unsupported encoding '{other}'; use utf-8, utf-16, windows-1252 or latin1")),
+    }
+}
+
+/// Most frequent of , ; tab | on the first line, outside quotes; ',' when none appears.
+pub fn detect_delimiter(text: &str) -> char {
+    let mut counts = [(',', 0), (';', 0), ('\t', 0), ('|', 0)];
+    let mut quoted = false;
+    for c in text.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted {
+            if c == '\n' || c == '\r' {
+                break;
+            }
+            if let Some(entry) = counts.iter_mut().find(|(d, _)| *d == c) {
+                entry.1 += 1;
+            }
+        }
+    }
+    counts.iter().max_by_key(|(_, n)| *n).filter(|(_, n)| *n > 0).map_or(',', |(d, _)| *d)
+}
+
+/// One record and the (1-based) line it starts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRecord {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+/// Split text into records; blank lines are skipped.
+pub fn parse_records(text: &str, delimiter: char) -> Result<Vec<CsvRecord>, String> {
+    let mut out = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut quote_line = 0;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.chars().peekable();
+
+    let mut end_record = |fields: &mut Vec<String>, field: &mut String, start_line: usize| {
+        fields.push(std::mem::take(field));
+        let record = std::mem::take(fields);
+        if !(record.len() == 1 && record[0].trim().is_empty()) {
+            out.push(CsvRecord { line: start_line, fields: record });
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push('\n');
+                }
+                '\r' if chars.peek() == Some(&'\n') => {}
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+                quote_line = line;
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                end_record(&mut fields, &mut field, start_line);
+                line += 1;
+                start_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("Line {quote_line}: quoted field is never closed"));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        end_record(&mut fields, &mut field, start_line);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(line: usize, fields: &[&str]) -> CsvRecord {
+        CsvRecord { line, fields: fields.iter().map(|f| f.to_string()).collect() }
+    }
+
+    #[test]
+    fn quoted_fields_keep_delimiters_quotes_and_line_breaks() {
+        assert_eq!(
+            parse_records("a,\"b,c\",\"d \"\"e\"\"\",\"f\ng\"\nh,i\n", ',').unwrap(),
+            vec![rec(1, &["a", "b,c", "d \"e\"", "f\ng"]), rec(3, &["h", "i"])]
+        );
+        // Spaces before the opening quote are dropped; a quote inside a value is literal
+        assert_eq!(parse_records(" \"x;y\";a\"b", ';').unwrap(), vec![rec(1, &["x;y", "a\"b"])]);
+    }
+
+    #[test]
+    fn empty_and_trailing_fields_are_kept() {
+        assert_eq!(
+            parse_records("a,,b,\n,,\n", ',').unwrap(),
+            vec![rec(1, &["a", "", "b", ""]), rec(2, &["", "", ""])]
+        );
+        assert_eq!(parse_records("\"\",x", ',').unwrap(), vec![rec(1, &["", "x"])]);
+    }
+
+    #[test]
+    fn crlf_lf_and_cr_line_endings() {
+        assert_eq!(parse_records("a,b\r\nc,d\r\n", ',').unwrap(), vec![rec(1, &["a", "b"]), rec(2, &["c", "d"])]);
+        assert_eq!(parse_records("a\rb", ',').unwrap(), vec![rec(1, &["a"]), rec(2, &["b"])]);
+        // CRLF inside quotes becomes a plain line break
+        assert_eq!(
+            parse_records("\"x\r\ny\",z\r\nw", ',').unwrap(),
+            vec![rec(1, &["x\ny", "z"]), rec(3, &["w"])]
+        );
+        assert_eq!(parse_records("last,line", ',').unwrap(), vec![rec(1, &["last", "line"])]);
+    }
+
+    #[test]
+    fn blank_lines_are_skipped_but_counted() {
+        assert_eq!(parse_records("a\n\n   \r\nb\n\n", ',').unwrap(), vec![rec(1, &["a"]), rec(4, &["b"])]);
+        assert!(parse_records("", ',').unwrap().is_empty());
+        assert!(parse_records("\n\r\n", ',').unwrap().is_empty());
+    }
+
+    #[test]
+    fn unclosed_quote_reports_the_line_it_opened_on() {
+        assert_eq!(parse_records("a,b\nc,\"d\ne,f\n", ',').unwrap_err(), "Line 2: quoted field is never closed");
+        assert_eq!(parse_records("\"a\nb\",c\n\"", ',').unwrap_err(), "Line 3: quoted field is never closed");
+    }
+
+    #[test]
+    fn delimiter_is_the_most_frequent_on_the_first_line() {
+        assert_eq!(detect_delimiter("a;b;c\n1,2,3,4,5"), ';');
+        assert_eq!(detect_delimiter("a\tb\tc,d"), '\t');
+        assert_eq!(detect_delimiter("a|b|c"), '|');
+        // Separators and line breaks inside quotes do not count
+        assert_eq!(detect_delimiter("\"x,y,z\";w;v"), ';');
+        assert_eq!(detect_delimiter("\"a\nb\";c;d\ne,f,g,h,i"), ';');
+        assert_eq!(detect_delimiter("single column"), ',');
+        assert_eq!(detect_delimiter(""), ',');
+    }
+
+    #[test]
+    fn byte_order_marks_win_over_the_requested_encoding() {
+        assert_eq!(decode_text(b"\xEF\xBB\xBFa;\xC3\xA9", Some("latin1")).unwrap(), "a;é");
+        assert_eq!(decode_text(b"\xFF\xFEa\0\xE9\0", None).unwrap(), "aé");
+        assert_eq!(decode_text(b"\xFE\xFF\0a\0\xE9", Some("windows-1252")).unwrap(), "aé");
+    }
+
+    #[test]
+    fn utf16_needs_an_even_number_of_bytes() {
+        let odd = "File is not valid UTF-16 (odd number of bytes)";
+        assert_eq!(decode_text(b"\xFF\xFEa\0b", None).unwrap_err(), odd);
+        assert_eq!(decode_text(b"\0a\0", Some("UTF_16BE")).unwrap_err(), odd);
+        assert_eq!(decode_text(b"a\0b\0", Some("utf-16")).unwrap(), "ab");
+        // Unpaired surrogate
+        assert_eq!(decode_text(b"\x00\xD8", Some("utf-16le")).unwrap_err(), "File is not valid UTF-16");
+    }
+
+    #[test]
+    fn windows_1252_maps_the_c1_range() {
+        let bytes = [0x80, 0x8A, 0x93, 0x94, 0x96, 0x99, 0x9F, 0x81, 0x8D, 0x8F, 0x90, 0x9D, 0xE9, b'a'];
+        assert_eq!(
+            decode_text(&bytes, Some("cp1252")).unwrap(),
+            "€Š“”–™Ÿ\u{fffd}\u{fffd}\u{fffd}\u{fffd}\u{fffd}éa"
+        );
+        // Latin-1 keeps the C1 controls as they are
+        assert_eq!(decode_text(&[0x80, 0xE9], Some("ISO-8859-1")).unwrap(), "\u{80}é");
+    }
+
+    #[test]
+    fn invalid_utf8_and_unknown_encodings_are_errors() {
+        assert_eq!(decode_text(b"caf\xE9", None).unwrap_err(), "File is not valid UTF-8; try windows-1252 or latin1");
+        assert!(decode_text(b"x", Some("ebcdic")).unwrap_err().starts_with("Unsupported encoding 'ebcdic'"));
+        assert_eq!(decode_text(b"x", Some(" UTF8 ")).unwrap(), "x");
+    }
+}
@@ -1,5 +1,9 @@
 // src/db.rs
-use rusqlite::{params, Connection};
+use crate::money::{is_valid_currency, FALLBACK_CURRENCY};
+use crate::phone::{is_valid_country_code, normalize_phone, FALLBACK_COUNTRY_CODE};
+use crate::reference::{render_reference, validate_reference_pattern, FALLBACK_REFERENCE_PATTERN};
+use crate::util::phone_key;
+use rusqlite::{params, Connection, OptionalExtension};
 use std::time::Duration;
 
 pub fn open_db(path: &std::path::PathBuf) -> rusqlite::Result<Connection> {
@@ -26,6 +30,39 @@ pub fn open_db(path: &std::path::PathBuf) -> rusqlite::Result<Connection> {
     Ok(conn)
 }
 
+/// In-memory database with the full schema, for tests.
+#[cfg(test)]
+pub(crate) fn open_test_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+    ensure_schema(&conn).unwrap();
+    conn
+}
+
+const FTS_COLUMNS: &str =
+    "reference, client_name, client_compact, phone, address, city, description, articles, tracking_number";
+
+// Values for the orders_fts columns (after rowid) from an orders row (`NEW.` or `orders.`).
+// client_compact glues multi-part names ("El-Idrissi" -> "ElIdrissi", and the whole
+// name as one word); phone carries the E.164 digits next to the number as typed.
+fn fts_values(row: &str) -> String {
+    format!(
+        "{row}id, COALESCE({row}reference, ''), {row}client_name,
+         replace(replace({row}client_name, '-', ''), '''', '') || ' ' ||
+           replace(replace(replace({row}client_name, ' ', ''), '-', ''), '''', ''),
+         replace({row}phone, '+', '') || ' ' || COALESCE({row}phone_raw, ''),
+         {row}address, {row}city, COALESCE({row}description, ''), {row}article_name,
+         COALESCE({row}tracking_number, '')"
+    )
+}
+
+// Helper: check if a table exists
+fn table_exists(conn: &Connection, table: &str) -> rusqlite::Result<bool> {
+    let mut stmt =
+        conn.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1 LIMIT 1")?;
+    stmt.exists(params![table])
+}
+
 // Helper: check if a column exists on a table (safe across versions)
 // Note: table identifier is injected into the PRAGMA; only used with static literals here.
 fn column_exists(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
@@ -47,6 +84,12 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
 
     // Do work; on error, roll back to the savepoint.
     let result: rusqlite::Result<()> = (|| {
+        // Tables whose first creation triggers a one-off backfill below
+        let seed_articles = !table_exists(conn, "articles")?;
+        let seed_reservations = !table_exists(conn, "stock_movements")?;
+        let seed_search = !table_exists(conn, "orders_fts")?;
+        let seed_customers = !table_exists(conn, "customers")?;
+
         // --- base tables ---
         conn.execute(
             r#"
@@ -54,17 +97,121 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
               id INTEGER PRIMARY KEY AUTOINCREMENT,
               client_name TEXT NOT NULL,
               article_name TEXT NOT NULL DEFAULT '',
-              phone TEXT NOT NULL,
+              phone TEXT NOT NULL,      -- E.164 when it could be parsed
+              phone_raw TEXT,           -- as typed, for display
               city TEXT NOT NULL,
               address TEXT NOT NULL,
               delivery_company TEXT NOT NULL,
               delivery_date TEXT NOT NULL,
               description TEXT,
               done INTEGER NOT NULL DEFAULT 0,
+              status TEXT NOT NULL DEFAULT 'pending',
+              completed_at TEXT, -- set when the order reaches a closed status
+              -- money in minor units, in the order's currency
+              subtotal INTEGER NOT NULL DEFAULT 0,
+              delivery_fee INTEGER NOT NULL DEFAULT 0,
+              discount INTEGER NOT NULL DEFAULT 0,
+              amount_to_collect INTEGER NOT NULL DEFAULT 0, -- cash on delivery
+              currency TEXT,
+              tracking_number TEXT, -- courier waybill
+              deleted_at TEXT, -- set while the order sits in the trash
+              reference TEXT, -- human-friendly number, see reference.rs
               created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
               -- may be added later via migration below
-              delivery_company_id INTEGER
+              delivery_company_id INTEGER,
                 -- (can't add FK constraint on ALTER, enforced via code + indexes)
+              customer_id INTEGER
+            )
+            "#,
+            [],
+        )?;
+        // Undo/redo: the order as it was before and after each journaled command
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS undo_journal (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              order_id INTEGER NOT NULL,
+              action TEXT NOT NULL,
+              before_state TEXT, -- JSON; NULL when the command created the order
+              after_state TEXT NOT NULL,
+              undone INTEGER NOT NULL DEFAULT 0, -- 1 while it sits on the redo side
+              batch_id INTEGER, -- id of the batch's first entry; undone and redone together
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              FOREIGN KEY(order_id) REFERENCES orders(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS delivery_attempts (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              order_id INTEGER NOT NULL,
+              attempted_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              outcome TEXT NOT NULL CHECK (outcome IN ('delivered','failed','rescheduled')),
+              reason TEXT,
+              note TEXT,
+              FOREIGN KEY(order_id) REFERENCES orders(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS returns (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              order_id INTEGER NOT NULL,
+              kind TEXT NOT NULL CHECK (kind IN ('return','exchange')),
+              reason TEXT,
+              restock INTEGER NOT NULL DEFAULT 0,
+              refund_amount INTEGER NOT NULL DEFAULT 0 CHECK (refund_amount >= 0),
+              replacement_order_id INTEGER, -- exchanges: the order shipping the new items
+              note TEXT,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              FOREIGN KEY(order_id) REFERENCES orders(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS return_items (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              return_id INTEGER NOT NULL,
+              order_item_id INTEGER, -- NULL once the order's items were edited away
+              article_id INTEGER,
+              article_name TEXT NOT NULL,
+              quantity INTEGER NOT NULL CHECK (quantity > 0),
+              FOREIGN KEY(return_id) REFERENCES returns(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS order_items (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              order_id INTEGER NOT NULL,
+              position INTEGER NOT NULL DEFAULT 0,
+              article_name TEXT NOT NULL,
+              quantity INTEGER NOT NULL DEFAULT 1 CHECK (quantity > 0),
+              unit_price INTEGER, -- minor units, NULL when unknown
+              note TEXT,
+              article_id INTEGER, -- catalog entry, when the name matches one
+              FOREIGN KEY(order_id) REFERENCES orders(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS order_events (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              order_id INTEGER NOT NULL, -- no FK: history outlives deleted orders
+              kind TEXT NOT NULL,        -- 'created' | 'status' | 'edited' | 'deleted' | 'restored' | 'return' | 'exchange' | 'undo' | 'redo'
+              old_value TEXT,            -- status name or JSON snapshot
+              new_value TEXT,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
             )
             "#,
             [],
@@ -95,11 +242,172 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
               id INTEGER PRIMARY KEY AUTOINCREMENT,
               name TEXT NOT NULL UNIQUE COLLATE NOCASE,
               active INTEGER NOT NULL DEFAULT 1,
+              default_fee INTEGER,           -- minor units, cities without their own rate
+              default_delivery_days INTEGER, -- promised days from order to delivery
+              restrict_to_listed_cities INTEGER NOT NULL DEFAULT 0,
+              contact_name TEXT,
+              phone TEXT,
+              email TEXT,
+              notes TEXT,
+              pickup_schedule TEXT,
+              account_number TEXT,
+              tracking_url_template TEXT, -- "{tracking}" is replaced by the tracking number
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS company_merges (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              source_id INTEGER NOT NULL, -- deleted row, kept for the record
+              source_name TEXT NOT NULL,
+              target_id INTEGER NOT NULL,
+              orders_moved INTEGER NOT NULL,
               created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
             )
             "#,
             [],
         )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS company_rates (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              delivery_company_id INTEGER NOT NULL,
+              city TEXT NOT NULL COLLATE NOCASE,
+              fee INTEGER NOT NULL CHECK (fee >= 0),
+              delivery_days INTEGER CHECK (delivery_days >= 0),
+              UNIQUE(delivery_company_id, city),
+              FOREIGN KEY(delivery_company_id) REFERENCES delivery_companies(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS customers (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              phone TEXT NOT NULL UNIQUE, -- normalized identity key
+              name TEXT NOT NULL,
+              city TEXT NOT NULL DEFAULT '',
+              address TEXT NOT NULL DEFAULT '',
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS customer_phone_aliases (
+              phone TEXT PRIMARY KEY, -- identity key of a customer merged away
+              customer_id INTEGER NOT NULL,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              FOREIGN KEY(customer_id) REFERENCES customers(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS articles (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+              sku TEXT UNIQUE COLLATE NOCASE,
+              default_price INTEGER CHECK (default_price IS NULL OR default_price >= 0), -- minor units
+              default_description TEXT,
+              active INTEGER NOT NULL DEFAULT 1,
+              low_stock_threshold INTEGER, -- NULL: no alert unless oversold
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS stock_movements (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              article_id INTEGER NOT NULL,
+              kind TEXT NOT NULL
+                CHECK (kind IN ('receipt','adjustment','reservation','release','shipment')),
+              quantity INTEGER NOT NULL, -- signed for adjustments, positive otherwise
+              order_id INTEGER,          -- no FK: movements outlive deleted orders
+              note TEXT,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              FOREIGN KEY(article_id) REFERENCES articles(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS settlements (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              delivery_company_id INTEGER NOT NULL,
+              date_from TEXT,
+              date_to TEXT,
+              expected_amount INTEGER NOT NULL, -- sum of the orders' amount to collect
+              received_amount INTEGER NOT NULL, -- cash actually handed over
+              currency TEXT NOT NULL,
+              note TEXT,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              FOREIGN KEY(delivery_company_id) REFERENCES delivery_companies(id)
+            )
+            "#,
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS settlement_orders (
+              settlement_id INTEGER NOT NULL,
+              order_id INTEGER NOT NULL UNIQUE, -- an order is settled once; no FK, kept after deletion
+              expected_amount INTEGER NOT NULL,
+              received_amount INTEGER NOT NULL,
+              note TEXT,
+              PRIMARY KEY(settlement_id, order_id),
+              FOREIGN KEY(settlement_id) REFERENCES settlements(id) ON DELETE CASCADE
+            )
+            "#,
+            [],
+        )?;
+        // Full-text index of orders, kept in sync by the tr_orders_fts_* triggers.
+        // Case and accents are folded ("Élodie" matches "elodie").
+        conn.execute(
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS orders_fts USING fts5(
+              reference, client_name, client_compact, phone, address, city,
+              description, articles, tracking_number,
+              tokenize = 'unicode61 remove_diacritics 2'
+            )
+            "#,
+            [],
+        )?;
+        // Last reference sequence handed out per (local) year
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS order_reference_seq (
+              year INTEGER PRIMARY KEY,
+              last_seq INTEGER NOT NULL
+            )
+            "#,
+            [],
+        )?;
+        // Named order-list filters; filter/sort hold the serialized OrderFilter/OrderSort
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS saved_views (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+              filter TEXT NOT NULL,
+              sort TEXT NOT NULL,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+            )
+            "#,
+            [],
+        )?;
         conn.execute(
             r#"
             CREATE TABLE IF NOT EXISTS settings (
@@ -127,6 +435,116 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
                 [],
             )?;
         }
+        if !column_exists(conn, "orders", "status")? {
+            conn.execute(
+                r#"ALTER TABLE orders ADD COLUMN status TEXT NOT NULL DEFAULT 'pending'"#,
+                [],
+            )?;
+            // Map the old open/finished flag onto the lifecycle
+            conn.execute(
+                r#"UPDATE orders SET status = CASE WHEN done = 1 THEN 'delivered' ELSE 'pending' END"#,
+                [],
+            )?;
+        }
+
+        if !column_exists(conn, "orders", "completed_at")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN completed_at TEXT"#, [])?;
+            // Orders closed before completion was tracked count from their creation,
+            // so settlements and revenue windows still see them
+            conn.execute(
+                r#"
+                UPDATE orders SET completed_at = created_at
+                WHERE completed_at IS NULL AND status IN ('delivered', 'returned', 'cancelled')
+                "#,
+                [],
+            )?;
+        }
+        if !column_exists(conn, "orders", "customer_id")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN customer_id INTEGER"#, [])?;
+        }
+
+        if !column_exists(conn, "undo_journal", "batch_id")? {
+            conn.execute(r#"ALTER TABLE undo_journal ADD COLUMN batch_id INTEGER"#, [])?;
+            conn.execute(r#"UPDATE undo_journal SET batch_id = id"#, [])?;
+        }
+
+        if !column_exists(conn, "order_items", "article_id")? {
+            conn.execute(r#"ALTER TABLE order_items ADD COLUMN article_id INTEGER"#, [])?;
+        }
+        if !column_exists(conn, "articles", "low_stock_threshold")? {
+            conn.execute(r#"ALTER TABLE articles ADD COLUMN low_stock_threshold INTEGER"#, [])?;
+        }
+        if !column_exists(conn, "delivery_companies", "default_fee")? {
+            conn.execute(r#"ALTER TABLE delivery_companies ADD COLUMN default_fee INTEGER"#, [])?;
+        }
+        if !column_exists(conn, "delivery_companies", "default_delivery_days")? {
+            conn.execute(
+                r#"ALTER TABLE delivery_companies ADD COLUMN default_delivery_days INTEGER"#,
+                [],
+            )?;
+        }
+        if !column_exists(conn, "delivery_companies", "restrict_to_listed_cities")? {
+            conn.execute(
+                r#"ALTER TABLE delivery_companies ADD COLUMN restrict_to_listed_cities INTEGER NOT NULL DEFAULT 0"#,
+                [],
+            )?;
+        }
+        for col in [
+            "contact_name",
+            "phone",
+            "email",
+            "notes",
+            "pickup_schedule",
+            "account_number",
+            "tracking_url_template",
+        ] {
+            if !column_exists(conn, "delivery_companies", col)? {
+                conn.execute(&format!("ALTER TABLE delivery_companies ADD COLUMN {col} TEXT"), [])?;
+            }
+        }
+        if !column_exists(conn, "orders", "amount_to_collect")? {
+            for col in ["subtotal", "delivery_fee", "discount", "amount_to_collect"] {
+                if !column_exists(conn, "orders", col)? {
+                    conn.execute(
+                        &format!("ALTER TABLE orders ADD COLUMN {col} INTEGER NOT NULL DEFAULT 0"),
+                        [],
+                    )?;
+                }
+            }
+            // Priced items recorded so far become the subtotal and the amount due
+            conn.execute(
+                r#"
+                UPDATE orders SET
+                  subtotal = COALESCE((SELECT SUM(i.quantity * COALESCE(i.unit_price, 0))
+                                       FROM order_items i WHERE i.order_id = orders.id), 0),
+                  amount_to_collect = COALESCE((SELECT SUM(i.quantity * COALESCE(i.unit_price, 0))
+                                                FROM order_items i WHERE i.order_id = orders.id), 0)
+                "#,
+                [],
+            )?;
+        }
+        // Orders saved before currencies existed were in today's default currency
+        if !column_exists(conn, "orders", "currency")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN currency TEXT"#, [])?;
+            conn.execute(
+                r#"UPDATE orders SET currency = ?1 WHERE currency IS NULL"#,
+                [default_currency(conn)?],
+            )?;
+        }
+        if !column_exists(conn, "orders", "tracking_number")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN tracking_number TEXT"#, [])?;
+        }
+        if !column_exists(conn, "orders", "deleted_at")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN deleted_at TEXT"#, [])?;
+        }
+        let backfill_refs = !column_exists(conn, "orders", "reference")?;
+        if backfill_refs {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN reference TEXT"#, [])?;
+        }
+        if !column_exists(conn, "orders", "phone_raw")? {
+            conn.execute(r#"ALTER TABLE orders ADD COLUMN phone_raw TEXT"#, [])?;
+            migrate_phones_to_e164(conn)?;
+        }
 
         // --- backfill & sync ---
         conn.execute(
@@ -150,6 +568,68 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
             "#,
             [],
         )?;
+        // Legacy single-article orders become one item with quantity 1
+        conn.execute(
+            r#"
+            INSERT INTO order_items(order_id, position, article_name, quantity)
+            SELECT o.id, 0, TRIM(o.article_name), 1
+            FROM orders o
+            WHERE TRIM(o.article_name) <> ''
+              AND NOT EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = o.id)
+            "#,
+            [],
+        )?;
+
+        if seed_customers {
+            backfill_customers(conn)?;
+        }
+        if backfill_refs {
+            backfill_references(conn)?;
+        }
+        if seed_search {
+            conn.execute(
+                &format!("INSERT INTO orders_fts (rowid, {FTS_COLUMNS}) SELECT {} FROM orders", fts_values("orders.")),
+                [],
+            )?;
+        }
+        if seed_articles {
+            // Seed the catalog once from article names already used on orders
+            // (order_items carries the historical orders.article_name values)
+            conn.execute(
+                r#"
+                INSERT OR IGNORE INTO articles(name)
+                SELECT DISTINCT TRIM(article_name)
+                FROM order_items
+                WHERE TRIM(article_name) <> ''
+                "#,
+                [],
+            )?;
+            conn.execute(
+                r#"
+                UPDATE order_items
+                SET article_id = (
+                  SELECT id FROM articles
+                  WHERE name = TRIM(order_items.article_name) COLLATE NOCASE
+                )
+                WHERE article_id IS NULL
+                "#,
+                [],
+            )?;
+        }
+        if seed_reservations {
+            // Orders still waiting to ship hold their stock from day one
+            conn.execute(
+                r#"
+                INSERT INTO stock_movements(article_id, kind, quantity, order_id)
+                SELECT i.article_id, 'reservation', SUM(i.quantity), i.order_id
+                FROM order_items i
+                JOIN orders o ON o.id = i.order_id
+                WHERE o.status IN ('pending','confirmed') AND i.article_id IS NOT NULL
+                GROUP BY i.order_id, i.article_id
+                "#,
+                [],
+            )?;
+        }
 
         // --- indexes ---
         conn.execute(
@@ -168,6 +648,110 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
             r#"CREATE INDEX IF NOT EXISTS idx_orders_done_created_at ON orders(done, created_at DESC)"#,
             [],
         )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_customer_id ON orders(customer_id, created_at DESC)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_status_delivery_date ON orders(status, delivery_date)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_order_events_order_id ON order_events(order_id, id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_order_events_kind_value ON order_events(kind, new_value, created_at)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_order_items_order_id ON order_items(order_id, position)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_order_items_article_id ON order_items(article_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_company_status ON orders(delivery_company_id, status, completed_at)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_delivery_attempts_order ON delivery_attempts(order_id, outcome)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_returns_order ON returns(order_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_undo_journal_order ON undo_journal(order_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_undo_journal_batch ON undo_journal(batch_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_return_items_return ON return_items(return_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_settlements_company ON settlements(delivery_company_id, created_at)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_stock_movements_article ON stock_movements(article_id, kind)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_stock_movements_order ON stock_movements(order_id)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_reference ON orders(reference)"#,
+            [],
+        )?;
+        // Trash only: a full index on deleted_at would win over the sort indexes below
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_trash ON orders(deleted_at) WHERE deleted_at IS NOT NULL"#,
+            [],
+        )?;
+        // Sort orders of query_orders, over the orders outside the trash
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_active_created ON orders(created_at, id) WHERE deleted_at IS NULL"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_active_delivery ON orders(delivery_date, id) WHERE deleted_at IS NULL"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_active_client ON orders(client_name COLLATE NOCASE, id) WHERE deleted_at IS NULL"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_orders_active_city ON orders(city COLLATE NOCASE, id) WHERE deleted_at IS NULL"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_articles_active_name ON articles(active, name)"#,
+            [],
+        )?;
+        conn.execute(
+            r#"CREATE INDEX IF NOT EXISTS idx_order_items_article_name ON order_items(article_name)"#,
+            [],
+        )?;
+
+        // --- views ---
+        // Orders outside the trash; every listing and statistic reads from here
+        conn.execute(
+            r#"
+            CREATE VIEW IF NOT EXISTS active_orders AS
+            SELECT * FROM orders WHERE deleted_at IS NULL
+            "#,
+            [],
+        )?;
 
         // --- triggers ---
         conn.execute(
@@ -197,6 +781,53 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
             "#,
             [],
         )?;
+        // `done` mirrors the status for older readers: closed states count as done
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS tr_orders_sync_done
+            AFTER UPDATE OF status ON orders
+            BEGIN
+              UPDATE orders
+              SET done = (NEW.status IN ('delivered', 'returned', 'cancelled'))
+              WHERE id = NEW.id;
+            END;
+            "#,
+            [],
+        )?;
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS tr_orders_fts_insert
+                 AFTER INSERT ON orders
+                 BEGIN
+                   INSERT INTO orders_fts (rowid, {FTS_COLUMNS}) VALUES ({});
+                 END;",
+                fts_values("NEW.")
+            ),
+            [],
+        )?;
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS tr_orders_fts_update
+                 AFTER UPDATE OF reference, client_name, phone, phone_raw, address, city,
+                                 description, article_name, tracking_number ON orders
+                 BEGIN
+                   DELETE FROM orders_fts WHERE rowid = OLD.id;
+                   INSERT INTO orders_fts (rowid, {FTS_COLUMNS}) VALUES ({});
+                 END;",
+                fts_values("NEW.")
+            ),
+            [],
+        )?;
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS tr_orders_fts_delete
+            AFTER DELETE ON orders
+            BEGIN
+              DELETE FROM orders_fts WHERE rowid = OLD.id;
+            END;
+            "#,
+            [],
+        )?;
 
         Ok(())
     })();
@@ -216,7 +847,8 @@ pub fn ensure_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
 }
 
 
-// Create (if needed) and fetch a delivery company id + canonicalized name
+// Create (if needed) and fetch a delivery company id + canonicalized name;
+// a name merged into another company resolves to that company
 pub fn get_or_create_delivery_company(
     conn: &Connection,
     name: &str,
@@ -228,13 +860,414 @@ pub fn get_or_create_delivery_company(
         return Err(rusqlite::Error::QueryReturnedNoRows);
     }
 
+    let by_name = |conn: &Connection| {
+        conn.query_row(
+            r#"SELECT id, name FROM delivery_companies WHERE name = ?1 COLLATE NOCASE"#,
+            [trimmed],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    };
+    if let Some(found) = by_name(conn)? {
+        return Ok(found);
+    }
+
+    // A spelling merged away leads to the company it was merged into (or that one's
+    // own merge target); ids are never reused, so the chain ends
+    let mut merged_into: Option<i64> = conn
+        .query_row(
+            r#"
+            SELECT target_id FROM company_merges
+            WHERE source_name = ?1 COLLATE NOCASE
+            ORDER BY id DESC LIMIT 1
+            "#,
+            [trimmed],
+            |r| r.get(0),
+        )
+        .optional()?;
+    while let Some(id) = merged_into {
+        let found = conn
+            .query_row(
+                r#"SELECT id, name FROM delivery_companies WHERE id = ?1"#,
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some(found) = found {
+            return Ok(found);
+        }
+        merged_into = conn
+            .query_row(
+                r#"SELECT target_id FROM company_merges WHERE source_id = ?1 ORDER BY id DESC LIMIT 1"#,
+                [id],
+                |r| r.get(0),
+            )
+            .optional()?;
+    }
+
     conn.execute(
         r#"INSERT OR IGNORE INTO delivery_companies(name) VALUES (TRIM(?1))"#,
         params![trimmed],
     )?;
+    by_name(conn)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+}
+
+pub fn read_setting(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(r#"SELECT value FROM settings WHERE key = ?1"#, [key], |r| r.get(0))
+        .optional()
+}
+
+// Country code applied to national numbers ("0612…"), from the `defaultCountryCode` setting
+pub fn default_country_code(conn: &Connection) -> Result<String, rusqlite::Error> {
+    let cc = read_setting(conn, "defaultCountryCode")?
+        .map(|v| v.trim().trim_start_matches('+').to_string())
+        .filter(|v| is_valid_country_code(v));
+    Ok(cc.unwrap_or_else(|| FALLBACK_COUNTRY_CODE.to_string()))
+}
+
+// Currency new orders are priced in, from the `currency` setting
+pub fn default_currency(conn: &Connection) -> Result<String, rusqlite::Error> {
+    let code = read_setting(conn, "currency")?
+        .map(|v| v.trim().to_ascii_uppercase())
+        .filter(|v| is_valid_currency(v));
+    Ok(code.unwrap_or_else(|| FALLBACK_CURRENCY.to_string()))
+}
+
+/// `orderReferencePattern` setting, or the fallback when missing or invalid.
+pub fn reference_pattern(conn: &Connection) -> Result<String, rusqlite::Error> {
+    let pattern = read_setting(conn, "orderReferencePattern")?
+        .filter(|p| validate_reference_pattern(p).is_ok());
+    Ok(pattern.unwrap_or_else(|| FALLBACK_REFERENCE_PATTERN.to_string()))
+}
+
+/// Next free reference for an order created in `year` (local time).
+/// Skips values already taken, e.g. after the pattern was changed back and forth.
+pub fn next_order_reference(conn: &Connection, year: i32) -> Result<String, rusqlite::Error> {
+    let pattern = reference_pattern(conn)?;
+    loop {
+        conn.execute(
+            r#"
+            INSERT INTO order_reference_seq (year, last_seq) VALUES (?1, 1)
+            ON CONFLICT(year) DO UPDATE SET last_seq = last_seq + 1
+            "#,
+            [year],
+        )?;
+        let seq: i64 = conn.query_row(
+            r#"SELECT last_seq FROM order_reference_seq WHERE year = ?1"#,
+            [year],
+            |r| r.get(0),
+        )?;
+        let reference = render_reference(&pattern, year, seq);
+        let taken: bool = conn.query_row(
+            r#"SELECT EXISTS(SELECT 1 FROM orders WHERE reference = ?1)"#,
+            [&reference],
+            |r| r.get(0),
+        )?;
+        if !taken {
+            return Ok(reference);
+        }
+    }
+}
+
+// Failed attempts after which an order goes back as returned; 0 turns it off
+pub const DEFAULT_MAX_FAILED_ATTEMPTS: i64 = 3;
+
+pub fn max_failed_attempts(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let n = read_setting(conn, "maxFailedAttempts")?
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|n| *n >= 0);
+    Ok(n.unwrap_or(DEFAULT_MAX_FAILED_ATTEMPTS))
+}
+
+pub const DEFAULT_UNDO_DEPTH: i64 = 50;
+
+/// How many changes can be undone; 0 turns the journal off.
+pub fn undo_depth(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    let n = read_setting(conn, "undoDepth")?
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|n| *n >= 0);
+    Ok(n.unwrap_or(DEFAULT_UNDO_DEPTH))
+}
+
+// One-off: keep what was typed in phone_raw and rewrite phone to E.164.
+// Numbers that cannot be parsed are left untouched.
+fn migrate_phones_to_e164(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let cc = default_country_code(conn)?;
+
+    conn.execute(r#"UPDATE orders SET phone_raw = phone WHERE phone_raw IS NULL"#, [])?;
+
+    let phones: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(r#"SELECT id, phone FROM orders"#)?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        let mut tmp = Vec::new();
+        for r in rows { tmp.push(r?); }
+        tmp
+    };
+    for (id, phone) in phones {
+        if let Ok(e164) = normalize_phone(&phone, &cc) {
+            if e164 != phone {
+                conn.execute(r#"UPDATE orders SET phone = ?1 WHERE id = ?2"#, params![e164, id])?;
+            }
+        }
+    }
+
+    // Re-key customers; numbers that now collide are folded into the oldest customer
+    let customers: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(r#"SELECT id, phone FROM customers ORDER BY id ASC"#)?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        let mut tmp = Vec::new();
+        for r in rows { tmp.push(r?); }
+        tmp
+    };
+    for (id, phone) in customers {
+        let Ok(e164) = normalize_phone(&phone, &cc) else { continue };
+        if e164 == phone {
+            continue;
+        }
+        let existing: Option<i64> = conn
+            .query_row(r#"SELECT id FROM customers WHERE phone = ?1"#, [&e164], |r| r.get(0))
+            .optional()?;
+        match existing {
+            Some(keep) => {
+                conn.execute(
+                    r#"UPDATE orders SET customer_id = ?1 WHERE customer_id = ?2"#,
+                    params![keep, id],
+                )?;
+                conn.execute(r#"DELETE FROM customers WHERE id = ?1"#, params![id])?;
+            }
+            None => {
+                conn.execute(
+                    r#"UPDATE customers SET phone = ?1 WHERE id = ?2"#,
+                    params![e164, id],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Link orders without a customer, oldest first so the latest contact details win
+fn backfill_customers(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let pending: Vec<(i64, String, String, String, String)> = {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, phone, client_name, city, address
+            FROM orders
+            WHERE customer_id IS NULL
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)))?;
+        let mut tmp = Vec::new();
+        for r in rows { tmp.push(r?); }
+        tmp
+    };
+
+    for (order_id, phone, name, city, address) in pending {
+        if let Some(customer_id) = upsert_customer(conn, &phone, &name, &city, &address)? {
+            conn.execute(
+                r#"UPDATE orders SET customer_id = ?1 WHERE id = ?2"#,
+                params![customer_id, order_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// Number older orders in creation order, per the year they were created
+fn backfill_references(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let pending: Vec<(i64, i32)> = {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, CAST(strftime('%Y', created_at, 'localtime') AS INTEGER)
+            FROM orders
+            WHERE reference IS NULL
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        let mut tmp = Vec::new();
+        for r in rows { tmp.push(r?); }
+        tmp
+    };
+
+    for (order_id, year) in pending {
+        let reference = next_order_reference(conn, year)?;
+        conn.execute(
+            r#"UPDATE orders SET reference = ?1 WHERE id = ?2"#,
+            params![reference, order_id],
+        )?;
+    }
+    Ok(())
+}
+
+// Create or refresh the customer behind a phone number; None when the phone has no digits
+pub fn upsert_customer(
+    conn: &Connection,
+    phone: &str,
+    name: &str,
+    city: &str,
+    address: &str,
+) -> Result<Option<i64>, rusqlite::Error> {
+    let key = phone_key(phone);
+    if key.is_empty() {
+        return Ok(None);
+    }
+
+    // A number merged into another customer keeps leading there
+    let merged_into: Option<i64> = conn
+        .query_row(
+            r#"SELECT customer_id FROM customer_phone_aliases WHERE phone = ?1"#,
+            params![key],
+            |r| r.get(0),
+        )
+        .optional()?;
+    if let Some(id) = merged_into {
+        conn.execute(
+            r#"
+            UPDATE customers SET
+              name = TRIM(?2),
+              city = TRIM(?3),
+              address = TRIM(?4),
+              updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE id = ?1
+            "#,
+            params![id, name, city, address],
+        )?;
+        return Ok(Some(id));
+    }
+
+    conn.execute(
+        r#"
+        INSERT INTO customers (phone, name, city, address)
+        VALUES (?1, TRIM(?2), TRIM(?3), TRIM(?4))
+        ON CONFLICT(phone) DO UPDATE SET
+          name = excluded.name,
+          city = excluded.city,
+          address = excluded.address,
+          updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        "#,
+        params![key, name, city, address],
+    )?;
 
-    let mut stmt = conn.prepare(
-        r#"SELECT id, name FROM delivery_companies WHERE name = ?1 COLLATE NOCASE"#,
+    let id: i64 = conn.query_row(
+        r#"SELECT id FROM customers WHERE phone = ?1"#,
+        params![key],
+        |r| r.get(0),
+    )?;
+    Ok(Some(id))
+}
+
+// Catalog entry matching an article name (case-insensitive): (id, canonical name, default price)
+pub fn find_article(
+    conn: &Connection,
+    name: &str,
+) -> Result<Option<(i64, String, Option<i64>)>, rusqlite::Error> {
+    conn.query_row(
+        r#"SELECT id, name, default_price FROM articles WHERE name = TRIM(?1) COLLATE NOCASE"#,
+        [name],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )
+    .optional()
+}
+
+// Append an entry to the order history (caller owns the transaction)
+pub fn record_order_event(
+    conn: &Connection,
+    order_id: i64,
+    kind: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        r#"
+        INSERT INTO order_events (order_id, kind, old_value, new_value)
+        VALUES (?1, ?2, ?3, ?4)
+        "#,
+        params![order_id, kind, old_value, new_value],
     )?;
-    stmt.query_row([trimmed], |row| Ok((row.get(0)?, row.get(1)?)))
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(conn: &Connection, source: i64, target: i64) {
+        let name: String =
+            conn.query_row("SELECT name FROM delivery_companies WHERE id = ?1", [source], |r| r.get(0)).unwrap();
+        conn.execute(
+            "INSERT INTO company_merges (source_id, source_name, target_id, orders_moved) VALUES (?1, ?2, ?3, 0)",
+            params![source, name, target],
+        )
+        .unwrap();
+        conn.execute("DELETE FROM delivery_companies WHERE id = ?1", [source]).unwrap();
+    }
+
+    #[test]
+    fn merged_company_spellings_resolve_to_the_target() {
+        let conn = open_test_db();
+        let (amana, _) = get_or_create_delivery_company(&conn, "Amana").unwrap();
+        let (typo, _) = get_or_create_delivery_company(&conn, "Amanaa").unwrap();
+        merge(&conn, typo, amana);
+        assert_eq!(get_or_create_delivery_company(&conn, " AMANAA ").unwrap(), (amana, "Amana".to_string()));
+
+        // The target merged further: follow it
+        let (express, _) = get_or_create_delivery_company(&conn, "Amana Express").unwrap();
+        merge(&conn, amana, express);
+        assert_eq!(get_or_create_delivery_company(&conn, "amanaa").unwrap().0, express);
+        assert_eq!(get_or_create_delivery_company(&conn, "Amana").unwrap().0, express);
+
+        let count = || -> i64 { conn.query_row("SELECT COUNT(*) FROM delivery_companies", [], |r| r.get(0)).unwrap() };
+        let before = count();
+        // A target deleted outright no longer captures the name
+        conn.execute("DELETE FROM delivery_companies WHERE id = ?1", [express]).unwrap();
+        let (again, name) = get_or_create_delivery_company(&conn, "Amanaa").unwrap();
+        assert!(again != express && name == "Amanaa");
+        assert_eq!(count(), before);
+        assert!(get_or_create_delivery_company(&conn, "  ").is_err());
+    }
+
+    #[test]
+    fn legacy_done_orders_become_delivered_with_a_completion_date() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE orders (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              client_name TEXT NOT NULL,
+              article_name TEXT NOT NULL DEFAULT '',
+              phone TEXT NOT NULL,
+              city TEXT NOT NULL,
+              address TEXT NOT NULL,
+              delivery_company TEXT NOT NULL,
+              delivery_date TEXT NOT NULL,
+              description TEXT,
+              done INTEGER NOT NULL DEFAULT 0,
+              created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+              delivery_company_id INTEGER
+            );
+            INSERT INTO orders (client_name, phone, city, address, delivery_company, delivery_date, done, created_at)
+            VALUES ('a', '0612345678', 'Rabat', 'x', 'Amana', '2024-01-01', 1, '2024-01-01T09:00:00.000Z'),
+                   ('b', '0612345678', 'Rabat', 'x', 'Amana', '2024-01-02', 0, '2024-01-02T09:00:00.000Z');
+            "#,
+        )
+        .unwrap();
+        ensure_schema(&conn).unwrap();
+
+        let rows: Vec<(String, Option<String>)> = conn
+            .prepare("SELECT status, completed_at FROM orders ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("delivered".to_string(), Some("2024-01-01T09:00:00.000Z".to_string())),
+                ("pending".to_string(), None),
+            ]
+        );
+    }
 }
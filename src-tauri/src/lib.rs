@@ -1,6 +1,11 @@
 // src/lib.rs
 mod app_state;
+mod csv;
 mod db;
+mod money;
+mod phone;
+mod query;
+mod reference;
 mod util;
 
 mod models {
@@ -8,6 +13,15 @@ mod models {
     pub mod company;
     pub mod theme;
     pub mod dashboard;
+    pub mod customer;
+    pub mod article;
+    pub mod inventory;
+    pub mod settlement;
+    pub mod delivery;
+    pub mod returns;
+    pub mod journal;
+    pub mod view;
+    pub mod import;
 }
 
 mod commands;
@@ -38,20 +52,91 @@ pub fn run() {
             commands::orders::get_order,
             commands::orders::update_order,
             commands::orders::set_order_done,
+            commands::orders::set_order_status,
             commands::orders::delete_order,
+            commands::orders::list_deleted_orders,
+            commands::orders::restore_order,
+            commands::orders::purge_deleted_orders,
             commands::orders::list_orders,
+            commands::orders::query_orders,
+            commands::orders::get_order_history,
+            // bulk actions
+            commands::bulk::bulk_set_status,
+            commands::bulk::bulk_set_delivery_company,
+            commands::bulk::bulk_reschedule_orders,
+            commands::bulk::bulk_delete_orders,
+            commands::bulk::bulk_open_orders,
+            // CSV import
+            commands::import::preview_csv,
+            commands::import::import_orders_csv,
+            // saved views
+            commands::views::list_saved_views,
+            commands::views::create_saved_view,
+            commands::views::update_saved_view,
+            commands::views::delete_saved_view,
+            commands::views::execute_view,
+            // undo / redo
+            commands::journal::undo_last,
+            commands::journal::redo_last,
+            // tracking & delivery attempts
+            commands::deliveries::set_order_tracking_number,
+            commands::deliveries::log_delivery_attempt,
+            commands::deliveries::list_delivery_attempts,
+            // returns & exchanges
+            commands::returns::create_return,
+            commands::returns::create_exchange,
+            commands::returns::list_returns,
             // opened stack
             commands::opened_orders::open_order,
             commands::opened_orders::get_opened_orders,
             commands::opened_orders::remove_opened_order,
             // search helpers
             commands::search::search_article_names,
+            commands::search::suggest_articles,
+            commands::search::check_article_name,
             commands::search::get_latest_description_for_article,
+            commands::search::search_order_references,
+            commands::search::search_orders,
+            commands::search::search_orders_dsl,
+            // article catalog
+            commands::articles::list_articles,
+            commands::articles::get_article,
+            commands::articles::create_article,
+            commands::articles::update_article,
+            commands::articles::set_article_active,
+            commands::articles::delete_article,
+            // inventory
+            commands::inventory::get_stock_levels,
+            commands::inventory::list_stock_movements,
+            commands::inventory::record_stock_movement,
+            commands::inventory::set_low_stock_threshold,
+            // courier settlements
+            commands::settlements::list_unsettled_orders,
+            commands::settlements::create_settlement,
+            commands::settlements::list_settlements,
+            commands::settlements::get_settlement,
+            commands::settlements::export_settlement_csv,
             // delivery companies (Settings.tsx)
             commands::companies::list_delivery_companies,
             commands::companies::add_delivery_company,
             commands::companies::set_delivery_company_active,
             commands::companies::rename_delivery_company,
+            commands::companies::merge_delivery_companies,
+            commands::companies::get_delivery_company,
+            commands::companies::update_delivery_company_details,
+            commands::companies::get_tracking_link,
+            commands::companies::get_company_rate_card,
+            commands::companies::set_company_delivery_defaults,
+            commands::companies::upsert_company_rate,
+            commands::companies::delete_company_rate,
+            commands::companies::suggest_delivery,
+            commands::companies::get_company_scorecards,
+            // customers
+            commands::customers::list_customers,
+            commands::customers::search_customers,
+            commands::customers::get_customer,
+            commands::customers::find_customer_by_phone,
+            commands::customers::merge_customers,
             // settings (Settings.tsx)
             commands::settings::get_setting,
             commands::settings::set_setting,
@@ -0,0 +1,43 @@
+// src/models/article.rs
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Article {
+    pub id: i64,
+    pub name: String,
+    pub sku: Option<String>,
+    pub default_price: Option<i64>, // minor units
+    pub default_description: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleInput {
+    pub name: String,
+    pub sku: Option<String>,
+    pub default_price: Option<i64>, // minor units
+    pub default_description: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// Autocomplete entry, best first.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleSuggestion {
+    pub name: String,
+    pub article_id: Option<i64>, // None for names only seen on orders
+    pub sku: Option<String>,
+    pub score: f64,              // 0..1, text match weighted with popularity
+    pub times_ordered: i64,
+}
+
+/// "Did you mean" for a newly typed article name.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleNameHint {
+    pub suggestion: String,
+    pub article_id: Option<i64>,
+    pub similarity: f64, // 0..1
+}
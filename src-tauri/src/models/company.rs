@@ -1,5 +1,5 @@
 // src/company.rs/company.rs
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -7,4 +7,84 @@ pub struct DeliveryCompany {
     pub id: i64,
     pub name: String,
     pub active: bool,
+    pub contact_name: Option<String>,
+    pub phone: Option<String>, // E.164
+    pub email: Option<String>,
+    pub notes: Option<String>,
+    pub pickup_schedule: Option<String>, // free text, e.g. "Mon-Fri 10:00-12:00"
+    pub account_number: Option<String>,
+    pub tracking_url_template: Option<String>, // e.g. "https://…/{tracking}"
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryCompanyDetailsInput {
+    pub contact_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub notes: Option<String>,
+    pub pickup_schedule: Option<String>,
+    pub account_number: Option<String>,
+    pub tracking_url_template: Option<String>,
+}
+
+/// Fee and promised delivery time for one city served by a company.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyRate {
+    pub id: i64,
+    pub city: String,
+    pub fee: i64,                   // minor units
+    pub delivery_days: Option<i64>, // falls back to the company default
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyRateInput {
+    pub city: String,
+    pub fee: i64,
+    pub delivery_days: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyRateCard {
+    pub delivery_company_id: i64,
+    pub default_fee: Option<i64>,
+    pub default_delivery_days: Option<i64>,
+    /// When set, only the listed cities are served.
+    pub restrict_to_listed_cities: bool,
+    pub rates: Vec<CompanyRate>,
+}
+
+/// What the order form should prefill for a company/city pair.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliverySuggestion {
+    pub delivery_company_id: Option<i64>, // None for a company not registered yet
+    pub fee: Option<i64>,
+    pub delivery_days: Option<i64>,
+    pub delivery_date: Option<String>, // yyyy-mm-dd, today + delivery_days
+    pub serves_city: bool,
+    pub warning: Option<String>,
+}
+
+/// Courier quality over a period; rates are None when there is nothing to measure.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyScorecard {
+    pub delivery_company_id: i64,
+    pub name: String,
+    pub active: bool,
+    pub orders: i64,          // created in the period
+    pub previous_orders: i64, // created in the period of the same length just before
+    pub volume_change_pct: Option<f64>,
+    pub delivered: i64,       // first delivered within the period
+    pub on_time_pct: Option<f64>,      // delivered on/before delivery_date
+    pub avg_days_late: Option<f64>,    // among late deliveries only
+    pub avg_transit_days: Option<f64>, // handed to courier (or created) -> delivered
+    pub returned: i64,
+    pub cancelled: i64,
+    pub return_rate_pct: Option<f64>, // returned / (delivered + returned), orders of the period
+    pub cancel_rate_pct: Option<f64>, // cancelled / orders of the period
 }
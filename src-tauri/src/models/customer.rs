@@ -0,0 +1,46 @@
+// src/models/customer.rs
+use crate::models::orders::OrderListItem;
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Customer {
+    pub id: i64,
+    pub phone: String, // normalized key
+    pub name: String,
+    pub city: String,
+    pub address: String,
+    pub order_count: i64,
+    pub last_order_at: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerStats {
+    pub order_count: i64,
+    pub open_count: i64,
+    pub delivered_count: i64,
+    pub returned_count: i64,
+    pub cancelled_count: i64,
+    pub total_units: i64,
+    pub first_order_at: Option<String>,
+    pub last_order_at: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerDetail {
+    pub customer: Customer,
+    pub stats: CustomerStats,
+    pub orders: Vec<OrderListItem>,
+}
+
+/// Last known contact details, offered to the order form for autofill.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerAutofill {
+    pub id: i64,
+    pub name: String,
+    pub city: String,
+    pub address: String,
+}
@@ -1,5 +1,6 @@
 // src/company.rs/dashboard.rs
 use serde::Serialize;
+use super::inventory::StockLevel;
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -13,11 +14,20 @@ pub struct Kpis {
     pub done_30d: i64,
     pub unique_clients: i64,
     pub returning_clients_pct: f64,
-    pub avg_lead_days: Option<f64>,
+    pub avg_lead_days: Option<f64>,    // planned: created -> delivery_date
     pub median_lead_days: Option<f64>,
+    pub avg_actual_lead_days: Option<f64>, // actual: created -> delivered event
+    pub median_actual_lead_days: Option<f64>,
+    pub on_time_rate_pct: Option<f64>, // delivered on/before delivery_date, last 90 days
     pub top_delivery_company: Option<TopItemShare>,
     pub top_article: Option<NameCount>,
     pub top_city: Option<NameCount>,
+    pub low_stock_articles: i64, // active articles oversold or at/below their threshold
+    // Cash from delivered orders, minor units in `currency`
+    pub currency: String,
+    pub revenue_7d: i64,
+    pub revenue_30d: i64,
+    pub avg_order_value_30d: Option<f64>,
 }
 
 #[derive(Serialize, Debug)]
@@ -32,6 +42,14 @@ pub struct TopItemShare {
 #[serde(rename_all = "camelCase")]
 pub struct NameCount { pub name: String, pub count: i64 }
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NameAmount { pub name: String, pub amount: i64, pub orders: i64 }
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnRate { pub name: String, pub shipped: i64, pub returned: i64, pub rate_pct: f64 }
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeCount { pub period: String, pub count: i64 }
@@ -52,6 +70,10 @@ pub struct LeadTimeBin { pub lead_days: i64, pub count: i64 }
 #[serde(rename_all = "camelCase")]
 pub struct BucketCount { pub bucket: String, pub count: i64 }
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusBucketCount { pub status: String, pub bucket: String, pub count: i64 }
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct HeatCell { pub weekday: i64, pub hour: i64, pub count: i64 }
@@ -72,6 +94,21 @@ pub struct OrderExceptionRow {
 #[serde(rename_all = "camelCase")]
 pub struct Exceptions {
     pub overdue_top10: Vec<OrderExceptionRow>,
+    pub repeated_failed_attempts: Vec<FailedAttemptsRow>, // still with the courier, 2+ failures
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedAttemptsRow {
+    pub id: i64,
+    pub article_name: String,
+    pub client_name: String,
+    pub city: String,
+    pub delivery_company: String,
+    pub tracking_number: Option<String>,
+    pub failed_attempts: i64,
+    pub last_attempt_at: String,
+    pub last_reason: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -84,8 +121,15 @@ pub struct DashboardData {
     pub lead_time_histogram: Vec<LeadTimeBin>,
     pub top_articles: Vec<NameCount>,
     pub company_share_90d: Vec<NameCount>,
+    pub revenue_by_company_90d: Vec<NameAmount>,
+    pub unsettled_cash_by_company: Vec<NameAmount>, // delivered, not yet settled with the courier
+    pub return_rate_by_article_90d: Vec<ReturnRate>, // units, top 10 by units returned
+    pub return_rate_by_company_90d: Vec<ReturnRate>, // orders
     pub new_vs_returning_monthly: Vec<(String, i64, i64)>, // (month, new, returning)
     pub backlog_age_buckets: Vec<BucketCount>,
+    pub backlog_age_buckets_by_status: Vec<StatusBucketCount>,
+    pub status_breakdown: Vec<NameCount>, // every status, including closed ones
     pub activity_heatmap: Vec<HeatCell>,
+    pub low_stock: Vec<StockLevel>, // shortest first, top 10
     pub exceptions: Exceptions,
 }
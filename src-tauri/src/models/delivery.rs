@@ -0,0 +1,23 @@
+// src/models/delivery.rs
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryAttempt {
+    pub id: i64,
+    pub order_id: i64,
+    pub attempted_at: String,
+    pub outcome: String, // "delivered" | "failed" | "rescheduled"
+    pub reason: Option<String>, // e.g. "no answer", "wrong address"
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryAttemptInput {
+    pub outcome: String,
+    pub reason: Option<String>,
+    pub note: Option<String>,
+    /// When the courier reported it; defaults to now.
+    pub attempted_at: Option<String>,
+}
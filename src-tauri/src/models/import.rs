@@ -0,0 +1,84 @@
+// src/models/import.rs
+use serde::{Deserialize, Serialize};
+
+/// CSV column feeding each order field: a header name (any case) or a 1-based
+/// column number. Unmapped optional fields are left empty.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvColumnMapping {
+    pub client_name: Option<String>,
+    pub phone: Option<String>,
+    pub city: Option<String>,
+    pub address: Option<String>,
+    pub delivery_company: Option<String>,
+    pub delivery_date: Option<String>,
+    pub description: Option<String>,
+    pub article_name: Option<String>,
+    pub quantity: Option<String>,
+    pub unit_price: Option<String>,   // amounts are read with two decimals, e.g. "25,50"
+    pub delivery_fee: Option<String>,
+    pub discount: Option<String>,
+}
+
+/// Day order of the date column; separators may be - / or .
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvDateFormat {
+    #[default]
+    Ymd,
+    Dmy,
+    Mdy,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvImportOptions {
+    pub mapping: CsvColumnMapping,
+    /// Single character; detected from the first line when empty.
+    #[serde(default)]
+    pub delimiter: Option<String>,
+    /// utf-8 (default), utf-16, windows-1252 or latin1.
+    #[serde(default)]
+    pub encoding: Option<String>,
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    #[serde(default)]
+    pub date_format: CsvDateFormat,
+    /// Courier for rows whose company column is empty or unmapped.
+    #[serde(default)]
+    pub default_delivery_company: Option<String>,
+    /// Import the valid rows even if others fail; by default any error imports nothing.
+    #[serde(default)]
+    pub skip_invalid: bool,
+}
+
+/// First records of a file, to build the column mapping.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPreview {
+    pub delimiter: String,
+    pub records: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvRowReport {
+    pub line: usize, // where the record starts in the file
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>, // e.g. likely duplicates; the row is still imported
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub imported: usize, // 0 on a dry run or when errors stopped the import
+    /// Only rows with errors or warnings.
+    pub rows: Vec<CsvRowReport>,
+}
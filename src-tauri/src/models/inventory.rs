@@ -0,0 +1,37 @@
+// src/models/inventory.rs
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StockLevel {
+    pub article_id: i64,
+    pub article_name: String,
+    pub sku: Option<String>,
+    pub on_hand: i64,  // receipts + adjustments - shipments
+    pub reserved: i64, // promised to open orders, not shipped yet
+    pub available: i64,
+    pub low_stock_threshold: Option<i64>,
+    pub low: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StockMovement {
+    pub id: i64,
+    pub article_id: i64,
+    pub kind: String, // "receipt" | "adjustment" | "reservation" | "release" | "shipment"
+    pub quantity: i64,
+    pub order_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// Manual movements only; reservations, releases and shipments follow the orders.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StockMovementInput {
+    pub article_id: i64,
+    pub kind: String, // "receipt" | "adjustment"
+    pub quantity: i64, // adjustments may be negative
+    pub note: Option<String>,
+}
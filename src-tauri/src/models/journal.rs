@@ -0,0 +1,15 @@
+// src/models/journal.rs
+use serde::Serialize;
+
+/// One undoable step, as returned by undo/redo; a bulk action is a single step
+/// reported by its first entry.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub id: i64,
+    pub order_id: i64,
+    /// Every order the step touched; more than one for bulk actions.
+    pub order_ids: Vec<i64>,
+    pub action: String, // command that made the change, e.g. "update_order"
+    pub created_at: String,
+}
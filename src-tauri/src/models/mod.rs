@@ -2,4 +2,13 @@
 pub mod orders;
 pub mod company;
 pub mod theme;
-pub mod dashboard;
\ No newline at end of file
+pub mod dashboard;
+pub mod customer;
+pub mod article;
+pub mod inventory;
+pub mod settlement;
+pub mod delivery;
+pub mod returns;
+pub mod journal;
+pub mod view;
+pub mod import;
\ No newline at end of file
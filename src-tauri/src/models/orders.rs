@@ -1,59 +1,453 @@
 // src/company.rs/orders.rs
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Lifecycle of an order:
+/// pending → confirmed → with_courier → delivered / returned,
+/// with cancellation possible until the parcel is handed to the courier.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    #[default]
+    Pending,
+    Confirmed,
+    WithCourier,
+    Delivered,
+    Returned,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub const ALL: [OrderStatus; 6] = [
+        OrderStatus::Pending,
+        OrderStatus::Confirmed,
+        OrderStatus::WithCourier,
+        OrderStatus::Delivered,
+        OrderStatus::Returned,
+        OrderStatus::Cancelled,
+    ];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pending" => Some(OrderStatus::Pending),
+            "confirmed" => Some(OrderStatus::Confirmed),
+            "with_courier" => Some(OrderStatus::WithCourier),
+            "delivered" => Some(OrderStatus::Delivered),
+            "returned" => Some(OrderStatus::Returned),
+            "cancelled" => Some(OrderStatus::Cancelled),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Confirmed => "confirmed",
+            OrderStatus::WithCourier => "with_courier",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Returned => "returned",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Confirmed)
+                | (Pending, Cancelled)
+                | (Confirmed, Pending)
+                | (Confirmed, WithCourier)
+                | (Confirmed, Cancelled)
+                | (WithCourier, Delivered)
+                | (WithCourier, Returned)
+                | (Delivered, Returned)
+                | (Cancelled, Pending)
+        )
+    }
+    /// Moves the legacy "done" checkbox makes from this status, in order.
+    /// Ticking walks the lifecycle up to delivered; unticking reopens a
+    /// confirmed, delivered or cancelled order as pending. Empty when already there.
+    pub fn done_toggle_path(&self, done: bool) -> Result<Vec<OrderStatus>, StatusError> {
+        use OrderStatus::*;
+        let path = match (self, done) {
+            (Pending, true) => vec![Confirmed, WithCourier, Delivered],
+            (Confirmed, true) => vec![WithCourier, Delivered],
+            (WithCourier, true) => vec![Delivered],
+            (Delivered, true) | (Pending, false) => vec![],
+            (Confirmed, false) | (Delivered, false) | (Cancelled, false) => vec![Pending],
+            (from, _) => {
+                let to = if done { Delivered } else { Pending };
+                return Err(StatusError::IllegalTransition { from: *from, to });
+            }
+        };
+        Ok(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusError {
+    Unknown(String),
+    IllegalTransition { from: OrderStatus, to: OrderStatus },
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusError::Unknown(s) => write!(f, "Unknown order status '{s}'"),
+            StatusError::IllegalTransition { from, to } => write!(
+                f,
+                "Cannot move an order from '{}' to '{}'",
+                from.as_str(),
+                to.as_str()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+fn default_quantity() -> i64 {
+    1
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItemInput {
+    /// Catalog entry picked in the form; otherwise matched by name.
+    #[serde(default)]
+    pub article_id: Option<i64>,
+    pub article_name: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i64,
+    pub unit_price: Option<i64>, // minor units (e.g. cents)
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItem {
+    pub id: i64,
+    pub article_id: Option<i64>,
+    pub article_name: String,
+    pub quantity: i64,
+    pub unit_price: Option<i64>,
+    pub note: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct NewOrderInput {
     pub client_name: String,
+    /// Legacy single-article field; used only when `items` is empty.
+    #[serde(default)]
     pub article_name: String,
+    #[serde(default)]
+    pub items: Vec<OrderItemInput>,
     pub phone: String,
     pub city: String,
     pub address: String,
     pub delivery_company: String,
     pub delivery_date: String, // yyyy-mm-dd
     pub description: Option<String>,
+    #[serde(default)]
+    pub delivery_fee: Option<i64>, // minor units
+    #[serde(default)]
+    pub discount: Option<i64>,     // minor units
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateOrderInput {
     pub client_name: String,
+    /// Legacy single-article field; used only when `items` is empty.
+    #[serde(default)]
     pub article_name: String,
+    #[serde(default)]
+    pub items: Vec<OrderItemInput>,
     pub phone: String,
     pub city: String,
     pub address: String,
     pub delivery_company: String,
     pub delivery_date: String, // yyyy-mm-dd
     pub description: Option<String>,
+    #[serde(default)]
+    pub delivery_fee: Option<i64>, // minor units
+    #[serde(default)]
+    pub discount: Option<i64>,     // minor units
 }
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderListItem {
     pub id: i64,
+    pub reference: String, // e.g. "ORD-2026-00042", safe to share with clients
+    pub article_name: String, // summary of the items, e.g. "Mug ×2, T-Shirt"
+    pub item_count: i64,
+    pub done: bool,
+    pub status: OrderStatus,
+}
+
+/// Filters for `query_orders`; empty fields do not filter.
+/// Dates are yyyy-mm-dd and inclusive; `created*` use the local calendar day.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OrderFilter {
+    pub status: Vec<OrderStatus>,
+    pub delivery_company_id: Option<i64>,
+    pub city: Option<String>,
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+    pub delivery_from: Option<String>,
+    pub delivery_to: Option<String>,
+    /// Matched against reference, client, phone, city, address, articles and tracking number.
+    pub text: Option<String>,
+    // Relative to today, so a saved view stays current: 0 = today, 1 = tomorrow, -1 = yesterday
+    pub delivery_from_days: Option<i64>,
+    pub delivery_to_days: Option<i64>,
+    /// Created at least this many days ago.
+    pub min_age_days: Option<i64>,
+    /// Pending, confirmed or with the courier.
+    pub open_only: bool,
+    /// Open and past its delivery date.
+    pub overdue: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSortKey {
+    #[default]
+    CreatedAt,
+    DeliveryDate,
+    ClientName,
+    City,
+    Reference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OrderSort {
+    pub key: OrderSortKey,
+    pub descending: bool,
+}
+
+impl Default for OrderSort {
+    fn default() -> Self {
+        OrderSort { key: OrderSortKey::CreatedAt, descending: true }
+    }
+}
+
+/// Row of the order list panel.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRow {
+    pub id: i64,
+    pub reference: String,
+    pub client_name: String,
     pub article_name: String,
+    pub item_count: i64,
+    pub city: String,
+    pub delivery_date: String,
+    pub delivery_company: String,
+    pub status: OrderStatus,
     pub done: bool,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderPage {
+    pub rows: Vec<OrderRow>,
+    pub total: i64, // all orders matching the filter, across pages
+    /// Pass back as `cursor` for the next page; None on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Piece of a search snippet; `matched` pieces are the highlighted terms.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub matched: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderSearchHit {
+    pub id: i64,
+    pub reference: String,
+    pub client_name: String,
+    pub city: String,
+    pub delivery_date: String,
+    pub status: OrderStatus,
+    pub snippet: Vec<SnippetPart>, // best matching field, trimmed around the hits
+}
+
+/// Order sitting in the trash, restorable until purged.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedOrder {
+    pub id: i64,
+    pub reference: String,
+    pub client_name: String,
+    pub article_name: String,
+    pub status: OrderStatus, // status at the time of deletion
+    pub deleted_at: String,
 }
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderWithId {
     pub id: i64,
+    pub reference: String,
     pub client_name: String,
     pub article_name: String,
-    pub phone: String,
+    pub phone: String,     // E.164
+    pub phone_raw: String, // as typed, for display
     pub city: String,
     pub address: String,
     pub delivery_company: String,
     pub delivery_date: String,
     pub description: Option<String>,
     pub done: bool,
+    pub status: OrderStatus,
+    pub items: Vec<OrderItem>,
+    // minor units, in `currency`
+    pub subtotal: i64,
+    pub delivery_fee: i64,
+    pub discount: i64,
+    pub amount_to_collect: i64,
+    pub currency: String,
+    pub tracking_number: Option<String>,
+    pub tracking_url: Option<String>, // from the company's tracking URL template
+    pub deleted_at: Option<String>,   // set while the order sits in the trash
 }
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenedOrderItem {
     pub order_id: i64,
-    pub article_name: String,
+    pub article_name: String, // items summary
+    pub item_count: i64,
     pub position: i64,
 }
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderEvent {
+    pub id: i64,
+    pub order_id: i64,
+    pub kind: String, // "created" | "status" | "edited" | "deleted" | "return" | "exchange"
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkOutcome {
+    Changed,
+    Unchanged, // already in the requested state
+    Failed,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub id: i64,
+    pub outcome: BulkOutcome,
+    pub error: Option<String>,
+}
+
+/// Per-order report of a bulk command; failed orders are left untouched.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkReport {
+    pub changed: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [OrderStatus; 6] = OrderStatus::ALL;
+
+    #[test]
+    fn parse_round_trips_and_ignores_case() {
+        for s in ALL {
+            assert_eq!(OrderStatus::parse(s.as_str()), Some(s));
+        }
+        assert_eq!(OrderStatus::parse(" With_Courier "), Some(OrderStatus::WithCourier));
+        assert_eq!(OrderStatus::parse("shipped"), None);
+        assert_eq!(OrderStatus::parse(""), None);
+    }
+
+    #[test]
+    fn lifecycle_allows_only_listed_moves() {
+        use OrderStatus::*;
+        let allowed = [
+            (Pending, Confirmed),
+            (Pending, Cancelled),
+            (Confirmed, Pending),
+            (Confirmed, WithCourier),
+            (Confirmed, Cancelled),
+            (WithCourier, Delivered),
+            (WithCourier, Returned),
+            (Delivered, Returned),
+            (Cancelled, Pending),
+        ];
+        for from in ALL {
+            for to in ALL {
+                assert_eq!(from.can_transition_to(to), allowed.contains(&(from, to)), "{from:?} -> {to:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_states_cannot_be_reopened_as_delivered_or_pending() {
+        assert!(!OrderStatus::Cancelled.can_transition_to(OrderStatus::Delivered));
+        assert!(!OrderStatus::Returned.can_transition_to(OrderStatus::Pending));
+        assert!(!OrderStatus::Delivered.can_transition_to(OrderStatus::Pending));
+    }
+
+    #[test]
+    fn done_checkbox_walks_the_lifecycle() {
+        use OrderStatus::*;
+        assert_eq!(Pending.done_toggle_path(true), Ok(vec![Confirmed, WithCourier, Delivered]));
+        assert_eq!(WithCourier.done_toggle_path(true), Ok(vec![Delivered]));
+        assert_eq!(Delivered.done_toggle_path(true), Ok(vec![]));
+        assert_eq!(Delivered.done_toggle_path(false), Ok(vec![Pending]));
+        assert_eq!(Cancelled.done_toggle_path(false), Ok(vec![Pending]));
+        assert_eq!(Pending.done_toggle_path(false), Ok(vec![]));
+        // Every forward step is an allowed move
+        for from in [Pending, Confirmed, WithCourier] {
+            let mut at = from;
+            for next in from.done_toggle_path(true).unwrap() {
+                assert!(at.can_transition_to(next), "{at:?} -> {next:?}");
+                at = next;
+            }
+        }
+    }
+
+    #[test]
+    fn done_checkbox_leaves_closed_orders_alone() {
+        use OrderStatus::*;
+        assert_eq!(
+            Cancelled.done_toggle_path(true),
+            Err(StatusError::IllegalTransition { from: Cancelled, to: Delivered })
+        );
+        assert_eq!(
+            Returned.done_toggle_path(false),
+            Err(StatusError::IllegalTransition { from: Returned, to: Pending })
+        );
+        assert!(WithCourier.done_toggle_path(false).is_err());
+    }
+
+    #[test]
+    fn status_errors_read_well() {
+        assert_eq!(StatusError::Unknown("x".into()).to_string(), "Unknown order status 'x'");
+        let e = StatusError::IllegalTransition { from: OrderStatus::Cancelled, to: OrderStatus::Delivered };
+        assert_eq!(e.to_string(), "Cannot move an order from 'cancelled' to 'delivered'");
+    }
+}
@@ -0,0 +1,75 @@
+// src/models/returns.rs
+use super::orders::OrderItemInput;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnItemInput {
+    pub order_item_id: i64,
+    pub quantity: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnInput {
+    pub reason: Option<String>,
+    /// Items coming back; empty means the whole order.
+    #[serde(default)]
+    pub items: Vec<ReturnItemInput>,
+    /// Put catalog items back into stock.
+    #[serde(default)]
+    pub restock: bool,
+    pub refund_amount: Option<i64>, // minor units
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeInput {
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub items: Vec<ReturnItemInput>,
+    #[serde(default)]
+    pub restock: bool,
+    /// What the client receives instead; shipped as a new linked order.
+    pub replacement_items: Vec<OrderItemInput>,
+    #[serde(default)]
+    pub delivery_fee: Option<i64>,
+    #[serde(default)]
+    pub discount: Option<i64>,
+    #[serde(default)]
+    pub delivery_date: String, // empty: from the company's promised delivery days
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnItem {
+    pub id: i64,
+    pub order_item_id: Option<i64>,
+    pub article_id: Option<i64>,
+    pub article_name: String,
+    pub quantity: i64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnRecord {
+    pub id: i64,
+    pub order_id: i64,
+    pub kind: String, // "return" | "exchange"
+    pub reason: Option<String>,
+    pub restock: bool,
+    pub refund_amount: i64,
+    pub replacement_order_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub items: Vec<ReturnItem>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeResult {
+    pub return_id: i64,
+    pub replacement_order_id: i64,
+}
@@ -0,0 +1,76 @@
+// src/models/settlement.rs
+use serde::{Deserialize, Serialize};
+
+/// Delivered order whose cash has not been settled with the courier yet.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsettledOrder {
+    pub order_id: i64,
+    pub client_name: String,
+    pub article_name: String,
+    pub city: String,
+    pub completed_at: Option<String>,
+    pub amount_to_collect: i64,
+    pub currency: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementLineInput {
+    pub order_id: i64,
+    pub received_amount: i64, // what the courier actually paid for this order
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementInput {
+    pub delivery_company_id: i64,
+    pub date_from: Option<String>, // yyyy-mm-dd, on the delivery (completion) date
+    pub date_to: Option<String>,
+    pub received_amount: i64,      // total cash handed over, minor units
+    /// Defaults to the currency setting.
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Orders paid differently from their amount to collect; the others are taken as paid in full.
+    #[serde(default)]
+    pub lines: Vec<SettlementLineInput>,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Settlement {
+    pub id: i64,
+    pub delivery_company_id: i64,
+    pub delivery_company: String,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub order_count: i64,
+    pub expected_amount: i64,
+    pub received_amount: i64,
+    pub discrepancy: i64,     // received - expected; negative when the courier is short
+    pub flagged_orders: i64,  // orders whose own amount does not match
+    pub currency: String,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementLine {
+    pub order_id: i64,
+    pub client_name: Option<String>, // None once the order was deleted
+    pub completed_at: Option<String>,
+    pub expected_amount: i64,
+    pub received_amount: i64,
+    pub discrepancy: i64,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementDetail {
+    pub settlement: Settlement,
+    pub lines: Vec<SettlementLine>,
+}
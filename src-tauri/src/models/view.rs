@@ -0,0 +1,24 @@
+// src/models/view.rs
+use crate::models::orders::{OrderFilter, OrderSort};
+use serde::{Deserialize, Serialize};
+
+/// Named order-list filter shown in the left panel.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedView {
+    pub id: i64,
+    pub name: String,
+    pub filter: OrderFilter,
+    pub sort: OrderSort,
+    pub count: i64, // matching orders right now, for the badge
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedViewInput {
+    pub name: String,
+    pub filter: OrderFilter,
+    pub sort: Option<OrderSort>,
+}
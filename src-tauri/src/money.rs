@@ -0,0 +1,160 @@
+// src/money.rs
+// Amounts are integer minor units (centimes, cents…) in the currency stored on
+// the order; nothing here ever touches floating point.
+
+/// Used when the `currency` setting is missing or invalid.
+pub const FALLBACK_CURRENCY: &str = "MAD";
+
+/// ISO 4217 style code: three ASCII letters, stored upper-case.
+pub fn is_valid_currency(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderTotals {
+    pub subtotal: i64,
+    pub delivery_fee: i64,
+    pub discount: i64,
+    /// Cash the courier collects on delivery: subtotal + delivery fee - discount.
+    pub amount_to_collect: i64,
+}
+
+/// Compute the order totals from `(quantity, unit_price)` lines.
+/// Lines without a price count as free; the discount may not exceed what is owed.
+pub fn compute_totals(
+    lines: impl IntoIterator<Item = (i64, Option<i64>)>,
+    delivery_fee: i64,
+    discount: i64,
+) -> Result<OrderTotals, String> {
+    if delivery_fee < 0 {
+        return Err("Delivery fee cannot be negative".to_string());
+    }
+    if discount < 0 {
+        return Err("Discount cannot be negative".to_string());
+    }
+
+    let overflow = || "Order total is too large".to_string();
+    let mut subtotal = 0_i64;
+    for (quantity, unit_price) in lines {
+        let line = quantity.checked_mul(unit_price.unwrap_or(0)).ok_or_else(overflow)?;
+        subtotal = subtotal.checked_add(line).ok_or_else(overflow)?;
+    }
+
+    let gross = subtotal.checked_add(delivery_fee).ok_or_else(overflow)?;
+    if discount > gross {
+        return Err(format!(
+            "Discount ({discount}) exceeds the order total ({gross})"
+        ));
+    }
+
+    Ok(OrderTotals {
+        subtotal,
+        delivery_fee,
+        discount,
+        amount_to_collect: gross - discount,
+    })
+}
+
+/// Read a typed amount ("25", "25.5", "1 234,50", "1,234.50") as minor units,
+/// assuming two decimals. The last '.' or ',' followed by one or two digits is
+/// the decimal mark; other separators group thousands.
+pub fn parse_amount(text: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid amount '{}'", text.trim());
+    let cleaned: String = text.trim().chars().filter(|c| !matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\'')).collect();
+    if cleaned.is_empty() || !cleaned.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return Err(invalid());
+    }
+
+    let (whole, fraction) = match cleaned.rfind(['.', ',']) {
+        Some(at) if (1..=2).contains(&(cleaned.len() - at - 1)) => (&cleaned[..at], &cleaned[at + 1..]),
+        _ => (cleaned.as_str(), ""),
+    };
+    let digits: String = whole.chars().filter(char::is_ascii_digit).collect();
+    if digits.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    let units: i64 = if digits.is_empty() { 0 } else { digits.parse().map_err(|_| invalid())? };
+    let cents: i64 = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<i64>().map_err(|_| invalid())? * 10,
+        _ => fraction.parse().map_err(|_| invalid())?,
+    };
+    units.checked_mul(100).and_then(|u| u.checked_add(cents)).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currency_codes_are_three_letters() {
+        assert!(is_valid_currency("MAD"));
+        assert!(is_valid_currency("eur"));
+        assert!(!is_valid_currency("EU"));
+        assert!(!is_valid_currency("EURO"));
+        assert!(!is_valid_currency("E1R"));
+        assert!(!is_valid_currency(""));
+    }
+
+    #[test]
+    fn totals_add_lines_and_fee_minus_discount() {
+        let t = compute_totals([(2, Some(1500)), (1, Some(999)), (3, None)], 3000, 500).unwrap();
+        assert_eq!(
+            t,
+            OrderTotals { subtotal: 3999, delivery_fee: 3000, discount: 500, amount_to_collect: 6499 }
+        );
+        let empty = compute_totals([], 0, 0).unwrap();
+        assert_eq!(empty.amount_to_collect, 0);
+    }
+
+    #[test]
+    fn discount_may_cover_the_whole_order_but_not_more() {
+        assert_eq!(compute_totals([(1, Some(1000))], 200, 1200).unwrap().amount_to_collect, 0);
+        assert_eq!(
+            compute_totals([(1, Some(1000))], 200, 1201).unwrap_err(),
+            "Discount (1201) exceeds the order total (1200)"
+        );
+    }
+
+    #[test]
+    fn totals_reject_negative_amounts_and_overflow() {
+        assert_eq!(compute_totals([], -1, 0).unwrap_err(), "Delivery fee cannot be negative");
+        assert_eq!(compute_totals([], 0, -1).unwrap_err(), "Discount cannot be negative");
+        assert_eq!(compute_totals([(2, Some(i64::MAX))], 0, 0).unwrap_err(), "Order total is too large");
+        assert_eq!(
+            compute_totals([(1, Some(i64::MAX)), (1, Some(1))], 0, 0).unwrap_err(),
+            "Order total is too large"
+        );
+        assert_eq!(compute_totals([(1, Some(i64::MAX))], 1, 0).unwrap_err(), "Order total is too large");
+    }
+
+    #[test]
+    fn amounts_read_with_either_decimal_mark() {
+        assert_eq!(parse_amount("25"), Ok(2500));
+        assert_eq!(parse_amount(" 25.5 "), Ok(2550));
+        assert_eq!(parse_amount("25,50"), Ok(2550));
+        assert_eq!(parse_amount("0,05"), Ok(5));
+        assert_eq!(parse_amount(",5"), Ok(50));
+    }
+
+    #[test]
+    fn amounts_ignore_thousands_separators() {
+        assert_eq!(parse_amount("1 234,50"), Ok(123450));
+        assert_eq!(parse_amount("1,234.50"), Ok(123450));
+        assert_eq!(parse_amount("1.234.567,8"), Ok(123456780));
+        assert_eq!(parse_amount("1\u{a0}234"), Ok(123400));
+        assert_eq!(parse_amount("1'000"), Ok(100000));
+        // Three digits after the last mark are a thousands group, not decimals
+        assert_eq!(parse_amount("1.234"), Ok(123400));
+    }
+
+    #[test]
+    fn invalid_amounts_are_rejected() {
+        for text in ["", "  ", "abc", "-5", "12€", ".", ",,", "1e3"] {
+            assert_eq!(parse_amount(text), Err(format!("Invalid amount '{}'", text.trim())), "{text:?}");
+        }
+        assert!(parse_amount("99999999999999999999").is_err());
+        assert!(parse_amount("92233720368547758.08").is_err());
+        assert_eq!(parse_amount("92233720368547758.07"), Ok(i64::MAX));
+    }
+}
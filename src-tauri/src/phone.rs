@@ -0,0 +1,199 @@
+// src/phone.rs
+// Phone numbers are stored in E.164 ("+212612345678") so the same client typed
+// three different ways ("0612 34 56 78", "+212 6 12 34 56 78", "00212612345678")
+// resolves to one identity.
+use std::fmt;
+
+/// Used when the `defaultCountryCode` setting is missing or invalid.
+pub const FALLBACK_COUNTRY_CODE: &str = "212";
+
+// E.164 allows at most 15 digits; below 8 nothing real is dialable.
+const MIN_DIGITS: usize = 8;
+const MAX_DIGITS: usize = 15;
+
+// Typed between digits and ignored: "06 12-34.56", "(0612) 34/56"
+const SEPARATORS: [char; 7] = [' ', '-', '.', '(', ')', '/', '\u{a0}'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhoneError {
+    Empty,
+    InvalidCharacter { ch: char, position: usize },
+    MisplacedPlus { position: usize },
+    TooShort { digits: usize },
+    TooLong { digits: usize },
+    InvalidCountryCode,
+}
+
+impl PhoneError {
+    /// Stable machine-readable code for the frontend.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PhoneError::Empty => "empty",
+            PhoneError::InvalidCharacter { .. } => "invalid_character",
+            PhoneError::MisplacedPlus { .. } => "misplaced_plus",
+            PhoneError::TooShort { .. } => "too_short",
+            PhoneError::TooLong { .. } => "too_long",
+            PhoneError::InvalidCountryCode => "invalid_country_code",
+        }
+    }
+}
+
+impl fmt::Display for PhoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid phone number ({}): ", self.code())?;
+        match self {
+            PhoneError::Empty => write!(f, "the number is empty"),
+            PhoneError::InvalidCharacter { ch, position } => {
+                write!(f, "unexpected character '{ch}' at position {}", position + 1)
+            }
+            PhoneError::MisplacedPlus { position } => {
+                write!(f, "'+' is only allowed at the start (found at position {})", position + 1)
+            }
+            PhoneError::TooShort { digits } => {
+                write!(f, "{digits} digits is too short for a phone number")
+            }
+            PhoneError::TooLong { digits } => {
+                write!(f, "{digits} digits exceeds the {MAX_DIGITS}-digit international limit")
+            }
+            PhoneError::InvalidCountryCode => write!(f, "country code cannot start with 0"),
+        }
+    }
+}
+
+impl std::error::Error for PhoneError {}
+
+/// A country calling code is 1 to 3 digits, never starting with 0.
+pub fn is_valid_country_code(cc: &str) -> bool {
+    (1..=3).contains(&cc.len())
+        && cc.chars().all(|c| c.is_ascii_digit())
+        && !cc.starts_with('0')
+}
+
+/// SQL expression for the digits of a phone column, without '+' or any separator
+/// `normalize_phone` accepts, for matching typed fragments with LIKE.
+pub fn phone_digits_sql(column: &str) -> String {
+    let mut expr = format!("COALESCE({column}, '')");
+    for c in SEPARATORS.iter().chain(&['+']) {
+        expr = format!("replace({expr}, '{c}', '')");
+    }
+    expr
+}
+
+/// Canonicalize a phone number to E.164.
+///
+/// - `+…` and `00…` are read as international numbers.
+/// - A leading trunk `0` is replaced by `default_cc` ("0612…" → "+212612…").
+/// - Bare digits already starting with `default_cc` and long enough are kept as-is;
+///   anything else is treated as a national number without its trunk prefix.
+pub fn normalize_phone(input: &str, default_cc: &str) -> Result<String, PhoneError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(PhoneError::Empty);
+    }
+
+    let mut digits = String::with_capacity(trimmed.len());
+    let mut has_plus = false;
+    for (position, ch) in trimmed.chars().enumerate() {
+        match ch {
+            '0'..='9' => digits.push(ch),
+            '+' if position == 0 => has_plus = true,
+            '+' => return Err(PhoneError::MisplacedPlus { position }),
+            c if SEPARATORS.contains(&c) => {}
+            _ => return Err(PhoneError::InvalidCharacter { ch, position }),
+        }
+    }
+    if digits.is_empty() {
+        return Err(PhoneError::Empty);
+    }
+
+    let international = if has_plus {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else {
+        let cc = if is_valid_country_code(default_cc) { default_cc } else { FALLBACK_COUNTRY_CODE };
+        if let Some(national) = digits.strip_prefix('0') {
+            format!("{cc}{national}")
+        } else if digits.starts_with(cc) && digits.len() - cc.len() >= MIN_DIGITS {
+            digits
+        } else {
+            format!("{cc}{digits}")
+        }
+    };
+
+    if international.starts_with('0') {
+        return Err(PhoneError::InvalidCountryCode);
+    }
+    if international.len() < MIN_DIGITS {
+        return Err(PhoneError::TooShort { digits: international.len() });
+    }
+    if international.len() > MAX_DIGITS {
+        return Err(PhoneError::TooLong { digits: international.len() });
+    }
+
+    Ok(format!("+{international}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn national_numbers_take_the_default_country_code() {
+        assert_eq!(normalize_phone("0612 34 56 78", "212").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("(0612) 34-56.78", "212").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("06 12 34 56 78", "33").unwrap(), "+33612345678");
+        // Without the trunk 0, with or without the country code already typed
+        assert_eq!(normalize_phone("612345678", "212").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("212612345678", "212").unwrap(), "+212612345678");
+    }
+
+    #[test]
+    fn international_prefixes_are_kept() {
+        assert_eq!(normalize_phone("00212612345678", "33").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("+212 6 12 34 56 78", "33").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone(" +1 (415) 555-0100 ", "212").unwrap(), "+14155550100");
+    }
+
+    #[test]
+    fn invalid_default_country_code_uses_the_fallback() {
+        assert_eq!(normalize_phone("0612345678", "").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("0612345678", "0").unwrap(), "+212612345678");
+        assert_eq!(normalize_phone("0612345678", "abc").unwrap(), "+212612345678");
+        assert!(!is_valid_country_code(""));
+        assert!(!is_valid_country_code("1234"));
+        assert!(is_valid_country_code("1"));
+    }
+
+    #[test]
+    fn length_limits() {
+        assert_eq!(normalize_phone("+1234567", "212"), Err(PhoneError::TooShort { digits: 7 }));
+        assert_eq!(normalize_phone("0612", "212"), Err(PhoneError::TooShort { digits: 6 }));
+        assert!(normalize_phone("+12345678", "212").is_ok());
+        assert!(normalize_phone("+123456789012345", "212").is_ok());
+        assert_eq!(normalize_phone("+1234567890123456", "212"), Err(PhoneError::TooLong { digits: 16 }));
+    }
+
+    #[test]
+    fn rejects_letters_misplaced_plus_and_empty_input() {
+        assert_eq!(
+            normalize_phone("0612a45678", "212"),
+            Err(PhoneError::InvalidCharacter { ch: 'a', position: 4 })
+        );
+        assert_eq!(normalize_phone("06+12345678", "212"), Err(PhoneError::MisplacedPlus { position: 2 }));
+        assert_eq!(normalize_phone("   ", "212"), Err(PhoneError::Empty));
+        assert_eq!(normalize_phone("+ - .", "212"), Err(PhoneError::Empty));
+        assert_eq!(normalize_phone("+0612345678", "212"), Err(PhoneError::InvalidCountryCode));
+        assert_eq!(normalize_phone("000612345678", "212"), Err(PhoneError::InvalidCountryCode));
+    }
+
+    #[test]
+    fn errors_carry_a_code_and_a_one_based_position() {
+        let e = normalize_phone("06x", "212").unwrap_err();
+        assert_eq!(e.code(), "invalid_character");
+        assert_eq!(
+            e.to_string(),
+            "Invalid phone number (invalid_character): unexpected character 'x' at position 3"
+        );
+    }
+}
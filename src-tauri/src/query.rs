@@ -0,0 +1,630 @@
+// src/query.rs
+// Query language of the power-user search box, e.g.
+//   status:open company:amana city:rabat due:<today+3 article:"mug*"
+//   (city:rabat OR city:sale) -is:done client:"ben ali"
+// Terms are ANDed unless joined by OR; "-" or NOT negates, parentheses group.
+//   status:   pending, confirmed, with_courier, delivered, returned, cancelled,
+//             open or closed; several separated by commas
+//   is:       open, closed, overdue, done
+//   company, city, ref, tracking, article:  whole value, any case; * is a wildcard
+//   client, phone:  contains
+//   due, created:  yyyy-mm-dd, today, tomorrow, yesterday or today+N / today-N,
+//             after an optional < <= > >=, or a range FROM..TO (either end open)
+// Bare words match anywhere in the reference, client, phone, city, address,
+// articles or tracking number.
+// The result is a condition on `active_orders o`; user text only ever travels as a
+// bound parameter, never inside the SQL string.
+
+use crate::models::orders::OrderStatus;
+use crate::phone::phone_digits_sql;
+use crate::util::{escape_like, is_valid_ymd};
+use rusqlite::types::Value;
+use std::fmt;
+
+/// Columns matched by free text, on alias `o`.
+pub const TEXT_SEARCH_COLUMNS: [&str; 8] = [
+    "o.reference", "o.client_name", "o.phone", "o.phone_raw", "o.city",
+    "o.address", "o.article_name", "o.tracking_number",
+];
+
+const MAX_QUERY_LEN: usize = 500;
+const MAX_DEPTH: usize = 16;
+const MAX_RELATIVE_DAYS: i64 = 3650;
+const OPEN_STATUSES: &str = "o.status IN ('pending','confirmed','with_courier')";
+const CLOSED_STATUSES: &str = "o.status IN ('delivered','returned','cancelled')";
+const FIELDS: &str = "status, is, company, city, client, phone, article, ref, tracking, due or created";
+
+/// Invalid query; `position` is the 1-based character where the problem starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at position {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+fn fail<T>(position: usize, message: impl Into<String>) -> Result<T, QueryError> {
+    Err(QueryError { position, message: message.into() })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tok {
+    Open,
+    Close,
+    Minus,
+    Colon,
+    Op(&'static str), // only right after a colon
+    Word(String),
+    Quoted(String),
+}
+
+#[derive(Debug)]
+struct Token {
+    tok: Tok,
+    position: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let position = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                out.push(Token { tok: Tok::Open, position });
+                i += 1;
+            }
+            ')' => {
+                out.push(Token { tok: Tok::Close, position });
+                i += 1;
+            }
+            ':' => {
+                out.push(Token { tok: Tok::Colon, position });
+                i += 1;
+                let op = match (chars.get(i), chars.get(i + 1)) {
+                    (Some('<'), Some('=')) => Some("<="),
+                    (Some('>'), Some('=')) => Some(">="),
+                    (Some('<'), _) => Some("<"),
+                    (Some('>'), _) => Some(">"),
+                    (Some('='), _) => Some("="),
+                    _ => None,
+                };
+                if let Some(op) = op {
+                    out.push(Token { tok: Tok::Op(op), position: i + 1 });
+                    i += op.len();
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return fail(position, "Missing closing quote"),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&c) => {
+                            text.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                out.push(Token { tok: Tok::Quoted(text), position });
+                i += 1;
+            }
+            // A leading '-' negates; inside a word ("2026-10-01") it is plain text
+            '-' if chars.get(i + 1).is_some_and(|c| !c.is_whitespace() && *c != ')') => {
+                out.push(Token { tok: Tok::Minus, position });
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"():\"".contains(chars[i]) {
+                    i += 1;
+                }
+                out.push(Token { tok: Tok::Word(chars[start..i].iter().collect()), position });
+            }
+        }
+    }
+    Ok(out)
+}
+
+// LIKE pattern for a user value: wildcards escaped, then `*` turned into `%`
+fn like_pattern(value: &str) -> String {
+    escape_like(value).replace('*', "%")
+}
+
+// SQL date expression (with one parameter) for a date value
+fn date_value(text: &str, position: usize) -> Result<(&'static str, Value), QueryError> {
+    let lower = text.to_ascii_lowercase();
+    let offset = match lower.as_str() {
+        "today" => Some(0),
+        "tomorrow" => Some(1),
+        "yesterday" => Some(-1),
+        _ => match lower.strip_prefix("today") {
+            Some(rest) if rest.starts_with(['+', '-']) => {
+                let days = rest.strip_suffix('d').unwrap_or(rest);
+                match days.parse::<i64>() {
+                    Ok(n) if n.abs() <= MAX_RELATIVE_DAYS => Some(n),
+                    Ok(_) => return fail(position, format!("Relative dates must be within {MAX_RELATIVE_DAYS} days")),
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        },
+    };
+    match offset {
+        Some(n) => Ok(("date('now', 'localtime', ? || ' days')", Value::Integer(n))),
+        None if is_valid_ymd(text) => Ok(("date(?)", Value::Text(text.to_string()))),
+        None => fail(
+            position,
+            format!("Invalid date '{text}'; use yyyy-mm-dd, today, tomorrow, yesterday or today+N"),
+        ),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize, // position just past the input, for "expected more" errors
+    depth: usize,
+    args: Vec<Value>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|t| &t.tok)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |t| t.position)
+    }
+
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Word(w)) if w == word)
+    }
+
+    fn or_expr(&mut self) -> Result<String, QueryError> {
+        let mut parts = vec![self.and_expr()?];
+        while self.at_word("OR") {
+            self.pos += 1;
+            parts.push(self.and_expr()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { format!("({})", parts.join(" OR ")) })
+    }
+
+    fn and_expr(&mut self) -> Result<String, QueryError> {
+        let mut parts = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Tok::Close) => break,
+                Some(Tok::Word(w)) if w == "OR" => break,
+                Some(Tok::Word(w)) if w == "AND" && !parts.is_empty() => {
+                    self.pos += 1;
+                    parts.push(self.unary()?);
+                }
+                _ => parts.push(self.unary()?),
+            }
+        }
+        match parts.len() {
+            0 => fail(self.position(), "Expected a search term"),
+            1 => Ok(parts.remove(0)),
+            _ => Ok(format!("({})", parts.join(" AND "))),
+        }
+    }
+
+    fn unary(&mut self) -> Result<String, QueryError> {
+        if matches!(self.peek(), Some(Tok::Minus)) || self.at_word("NOT") {
+            self.pos += 1;
+            return Ok(format!("NOT {}", self.unary()?));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<String, QueryError> {
+        let position = self.position();
+        let Some(tok) = self.peek().cloned() else {
+            return fail(position, "Expected a search term");
+        };
+        match tok {
+            Tok::Open => {
+                if self.depth == MAX_DEPTH {
+                    return fail(position, "Too many nested parentheses");
+                }
+                self.pos += 1;
+                if matches!(self.peek(), Some(Tok::Close)) {
+                    return fail(position, "Empty parentheses");
+                }
+                self.depth += 1;
+                let inner = self.or_expr()?;
+                self.depth -= 1;
+                if !matches!(self.peek(), Some(Tok::Close)) {
+                    return fail(position, "Missing ')' for this '('");
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Tok::Word(name) if matches!(self.tokens.get(self.pos + 1).map(|t| &t.tok), Some(Tok::Colon)) => {
+                self.pos += 2;
+                self.field(&name, position)
+            }
+            Tok::Word(text) | Tok::Quoted(text) => {
+                self.pos += 1;
+                self.text(&text, position)
+            }
+            Tok::Close => fail(position, "Unexpected ')'"),
+            Tok::Colon => fail(position, "Missing field name before ':'"),
+            Tok::Op(op) => fail(position, format!("Unexpected '{op}'")),
+            Tok::Minus => fail(position, "Unexpected '-'"),
+        }
+    }
+
+    // Free text anywhere in the searchable columns
+    fn text(&mut self, text: &str, position: usize) -> Result<String, QueryError> {
+        if text.trim().is_empty() {
+            return fail(position, "Empty search term");
+        }
+        let pattern = format!("%{}%", like_pattern(text));
+        let like: Vec<String> = TEXT_SEARCH_COLUMNS
+            .iter()
+            .map(|c| format!("COALESCE({c}, '') LIKE ? ESCAPE '\\'"))
+            .collect();
+        self.args.extend(TEXT_SEARCH_COLUMNS.iter().map(|_| Value::Text(pattern.clone())));
+        Ok(format!("({})", like.join(" OR ")))
+    }
+
+    fn field(&mut self, name: &str, position: usize) -> Result<String, QueryError> {
+        let op = match self.peek() {
+            Some(Tok::Op(op)) => {
+                let op = (*op, self.position());
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        };
+        let value_position = self.position();
+        let value = match self.peek() {
+            Some(Tok::Word(v) | Tok::Quoted(v)) => v.clone(),
+            _ => return fail(value_position, format!("Missing value after '{name}:'")),
+        };
+        self.pos += 1;
+        if value.trim().is_empty() {
+            return fail(value_position, format!("Empty value after '{name}:'"));
+        }
+
+        let field = name.to_ascii_lowercase();
+        let is_date = matches!(field.as_str(), "due" | "created");
+        if let Some((op, op_position)) = op {
+            if op != "=" && !is_date {
+                return fail(op_position, format!("'{op}' only works with due: and created:"));
+            }
+        }
+        let op = op.map_or("=", |(op, _)| op);
+
+        match field.as_str() {
+            "status" => self.status(&value, value_position),
+            "is" => match value.to_ascii_lowercase().as_str() {
+                "open" => Ok(OPEN_STATUSES.to_string()),
+                "closed" => Ok(CLOSED_STATUSES.to_string()),
+                "overdue" => Ok(format!("({OPEN_STATUSES} AND date(o.delivery_date) < date('now','localtime'))")),
+                "done" => Ok("o.done = 1".to_string()),
+                _ => fail(value_position, format!("Unknown state '{value}'; use open, closed, overdue or done")),
+            },
+            "company" | "courier" => Ok(self.whole("o.delivery_company", &value)),
+            "city" => Ok(self.whole("o.city", &value)),
+            "ref" | "reference" => Ok(self.whole("o.reference", &value)),
+            "tracking" => Ok(self.whole("o.tracking_number", &value)),
+            "article" => Ok(format!(
+                "EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = o.id AND {})",
+                self.whole("i.article_name", &value)
+            )),
+            "client" | "name" => {
+                self.args.push(Value::Text(format!("%{}%", like_pattern(&value))));
+                Ok(r#"o.client_name LIKE ? ESCAPE '\'"#.to_string())
+            }
+            "phone" => {
+                let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+                if digits.is_empty() {
+                    return fail(value_position, "phone: needs at least one digit");
+                }
+                let pattern = format!("%{digits}%");
+                self.args.extend([Value::Text(pattern.clone()), Value::Text(pattern)]);
+                Ok(format!(
+                    "({} LIKE ? OR {} LIKE ?)",
+                    phone_digits_sql("o.phone"),
+                    phone_digits_sql("o.phone_raw")
+                ))
+            }
+            "due" => self.date("date(o.delivery_date)", op, &value, value_position),
+            "created" => self.date("date(o.created_at, 'localtime')", op, &value, value_position),
+            _ => fail(position, format!("Unknown field '{name}'; use {FIELDS}")),
+        }
+    }
+
+    // Whole-value match, or LIKE when the value has a `*`
+    fn whole(&mut self, column: &str, value: &str) -> String {
+        if value.contains('*') {
+            self.args.push(Value::Text(like_pattern(value)));
+            format!("COALESCE({column}, '') LIKE ? ESCAPE '\\'")
+        } else {
+            self.args.push(Value::Text(value.to_string()));
+            format!("COALESCE({column}, '') = ? COLLATE NOCASE")
+        }
+    }
+
+    fn status(&mut self, value: &str, position: usize) -> Result<String, QueryError> {
+        let mut statuses: Vec<&'static str> = Vec::new();
+        let mut offset = 0;
+        for part in value.split(',') {
+            let found = match part.trim().to_ascii_lowercase().as_str() {
+                "open" => vec!["pending", "confirmed", "with_courier"],
+                "closed" => vec!["delivered", "returned", "cancelled"],
+                other => match OrderStatus::parse(other) {
+                    Some(s) => vec![s.as_str()],
+                    None => return fail(position + offset, format!("Unknown status '{}'", part.trim())),
+                },
+            };
+            for s in found {
+                if !statuses.contains(&s) {
+                    statuses.push(s);
+                }
+            }
+            offset += part.chars().count() + 1;
+        }
+        self.args.extend(statuses.iter().map(|s| Value::Text(s.to_string())));
+        Ok(format!("o.status IN ({})", vec!["?"; statuses.len()].join(", ")))
+    }
+
+    fn date(&mut self, expr: &str, op: &str, value: &str, position: usize) -> Result<String, QueryError> {
+        let Some((from, to)) = value.split_once("..") else {
+            let (sql, arg) = date_value(value, position)?;
+            self.args.push(arg);
+            return Ok(format!("{expr} {op} {sql}"));
+        };
+        if op != "=" {
+            return fail(position, format!("A date range cannot follow '{op}'"));
+        }
+        let to_position = position + from.chars().count() + 2;
+        let mut conds = Vec::new();
+        for (bound, cmp, at) in [(from, ">=", position), (to, "<=", to_position)] {
+            if bound.is_empty() {
+                continue;
+            }
+            let (sql, arg) = date_value(bound, at)?;
+            self.args.push(arg);
+            conds.push(format!("{expr} {cmp} {sql}"));
+        }
+        if conds.is_empty() {
+            return fail(position, "A date range needs at least one end");
+        }
+        Ok(format!("({})", conds.join(" AND ")))
+    }
+}
+
+/// Compile a query into a condition on `active_orders o` and its parameters.
+/// An empty query matches every order.
+pub fn compile_query(input: &str) -> Result<(String, Vec<Value>), QueryError> {
+    let len = input.chars().count();
+    if len > MAX_QUERY_LEN {
+        return fail(MAX_QUERY_LEN + 1, format!("Queries are limited to {MAX_QUERY_LEN} characters"));
+    }
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(("1 = 1".to_string(), Vec::new()));
+    }
+    let mut parser = Parser { tokens, pos: 0, end: len + 1, depth: 0, args: Vec::new() };
+    let sql = parser.or_expr()?;
+    if parser.pos < parser.tokens.len() {
+        return fail(parser.position(), "Unexpected ')'");
+    }
+    Ok((sql, parser.args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::{params_from_iter, Connection};
+
+    fn sql(input: &str) -> (String, Vec<Value>) {
+        compile_query(input).unwrap()
+    }
+
+    fn err(input: &str) -> (usize, String) {
+        let e = compile_query(input).unwrap_err();
+        (e.position, e.message)
+    }
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    const CITY: &str = "COALESCE(o.city, '') = ? COLLATE NOCASE";
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(sql("   "), ("1 = 1".to_string(), vec![]));
+    }
+
+    #[test]
+    fn fields_compile_to_bound_conditions() {
+        assert_eq!(
+            sql("status:open"),
+            ("o.status IN (?, ?, ?)".to_string(), vec![text("pending"), text("confirmed"), text("with_courier")])
+        );
+        assert_eq!(
+            sql("status:Delivered,closed"),
+            ("o.status IN (?, ?, ?)".to_string(), vec![text("delivered"), text("returned"), text("cancelled")])
+        );
+        assert_eq!(
+            sql(r#"city:rabat company:"Amana Express""#),
+            (
+                format!("({CITY} AND COALESCE(o.delivery_company, '') = ? COLLATE NOCASE)"),
+                vec![text("rabat"), text("Amana Express")]
+            )
+        );
+        assert_eq!(
+            sql("article:mug*"),
+            (
+                r"EXISTS (SELECT 1 FROM order_items i WHERE i.order_id = o.id AND COALESCE(i.article_name, '') LIKE ? ESCAPE '\')"
+                    .to_string(),
+                vec![text("mug%")]
+            )
+        );
+        assert_eq!(sql("is:done").0, "o.done = 1");
+    }
+
+    #[test]
+    fn user_text_is_escaped_and_never_inlined() {
+        let (where_sql, args) = sql(r#"client:"50%_off" 'drop"#);
+        assert!(!where_sql.contains("50") && !where_sql.contains("drop"));
+        assert_eq!(args[0], text(r"%50\%\_off%"));
+        assert_eq!(args.len(), 1 + TEXT_SEARCH_COLUMNS.len());
+        assert!(args[1..].iter().all(|a| *a == text("%'drop%")));
+    }
+
+    #[test]
+    fn quoted_values_keep_spaces_and_escaped_quotes() {
+        assert_eq!(sql(r#"client:"ben ali""#).1, vec![text("%ben ali%")]);
+        assert_eq!(sql(r#"ref:"A \"B\" \\ C""#).1, vec![text(r#"A "B" \ C"#)]);
+        // Keywords and field syntax inside quotes are plain text
+        assert_eq!(sql(r#""city:x OR y""#).1[0], text("%city:x OR y%"));
+    }
+
+    #[test]
+    fn negation_or_and_grouping() {
+        assert_eq!(sql("-is:done").0, "NOT o.done = 1");
+        assert_eq!(sql("NOT city:a").0, format!("NOT {CITY}"));
+        assert_eq!(sql("city:a OR city:b").0, format!("({CITY} OR {CITY})"));
+        assert_eq!(sql("city:a AND city:b").0, sql("city:a city:b").0);
+        assert_eq!(
+            sql("(city:a OR city:b) -(city:c)").0,
+            format!("(({CITY} OR {CITY}) AND NOT {CITY})")
+        );
+        // AND binds tighter than OR
+        assert_eq!(sql("city:a city:b OR city:c").0, format!("(({CITY} AND {CITY}) OR {CITY})"));
+        // A dash inside a value is not a negation
+        assert_eq!(sql("ref:ORD-2026-1").1, vec![text("ORD-2026-1")]);
+    }
+
+    #[test]
+    fn dates_ranges_and_comparisons() {
+        const REL: &str = "date('now', 'localtime', ? || ' days')";
+        assert_eq!(
+            sql("due:2026-10-01..2026-10-31"),
+            (
+                "(date(o.delivery_date) >= date(?) AND date(o.delivery_date) <= date(?))".to_string(),
+                vec![text("2026-10-01"), text("2026-10-31")]
+            )
+        );
+        assert_eq!(
+            sql("due:..today+3"),
+            (format!("(date(o.delivery_date) <= {REL})"), vec![Value::Integer(3)])
+        );
+        assert_eq!(sql("due:today-2d..").1, vec![Value::Integer(-2)]);
+        assert_eq!(
+            sql("created:>=yesterday"),
+            (format!("date(o.created_at, 'localtime') >= {REL}"), vec![Value::Integer(-1)])
+        );
+        assert_eq!(sql("due:<tomorrow").0, format!("date(o.delivery_date) < {REL}"));
+        assert_eq!(sql("due:TODAY").1, vec![Value::Integer(0)]);
+    }
+
+    #[test]
+    fn invalid_dates_are_reported_where_they_start() {
+        let invalid = |d: &str| format!("Invalid date '{d}'; use yyyy-mm-dd, today, tomorrow, yesterday or today+N");
+        assert_eq!(err("due:2026-02-30"), (5, invalid("2026-02-30")));
+        assert_eq!(err("due:26-1-1"), (5, invalid("26-1-1")));
+        assert_eq!(err("due:2026-01-01..soon"), (17, invalid("soon")));
+        assert_eq!(err("due:today+9999"), (5, "Relative dates must be within 3650 days".to_string()));
+        assert_eq!(err("due:.."), (5, "A date range needs at least one end".to_string()));
+        assert_eq!(err("due:<today..today+1"), (6, "A date range cannot follow '<'".to_string()));
+    }
+
+    #[test]
+    fn unknown_fields_and_values() {
+        assert_eq!(err("colour:red"), (1, format!("Unknown field 'colour'; use {FIELDS}")));
+        assert_eq!(err("mug size:xl").0, 5);
+        assert_eq!(err("status:pending,shipped"), (16, "Unknown status 'shipped'".to_string()));
+        assert_eq!(err("is:late").0, 4);
+        assert_eq!(err("city:<a"), (6, "'<' only works with due: and created:".to_string()));
+        assert_eq!(err("phone:abc"), (7, "phone: needs at least one digit".to_string()));
+        assert_eq!(err(r#"city:"""#), (6, "Empty value after 'city:'".to_string()));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_problem() {
+        assert_eq!(err(r#"client:"ben"#), (8, "Missing closing quote".to_string()));
+        assert_eq!(err("(city:a"), (1, "Missing ')' for this '('".to_string()));
+        assert_eq!(err("city:a)"), (7, "Unexpected ')'".to_string()));
+        assert_eq!(err("a ()"), (3, "Empty parentheses".to_string()));
+        assert_eq!(err("city:"), (6, "Missing value after 'city:'".to_string()));
+        assert_eq!(err(":x"), (1, "Missing field name before ':'".to_string()));
+        assert_eq!(err("a OR"), (5, "Expected a search term".to_string()));
+        assert_eq!(err(&"(".repeat(MAX_DEPTH + 1)).0, MAX_DEPTH + 1);
+        assert_eq!(err(&"a".repeat(MAX_QUERY_LEN + 1)).0, MAX_QUERY_LEN + 1);
+        let e = compile_query("(").unwrap_err();
+        assert_eq!(e.to_string(), "Expected a search term (at position 2)");
+    }
+
+    #[test]
+    fn phone_matches_any_typed_format() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE active_orders (id INTEGER PRIMARY KEY, phone TEXT, phone_raw TEXT);
+             INSERT INTO active_orders (phone, phone_raw) VALUES
+               ('+212612345678', '(0612) 34/56.78'),
+               ('+212699999999', NULL);",
+        )
+        .unwrap();
+        let count = |q: &str| -> i64 {
+            let (where_sql, args) = sql(q);
+            conn.query_row(
+                &format!("SELECT COUNT(*) FROM active_orders o WHERE {where_sql}"),
+                params_from_iter(args.iter()),
+                |r| r.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(count(r#"phone:"(0612) 34""#), 1);
+        assert_eq!(count("phone:0612-345"), 1);
+        assert_eq!(count("phone:+212-6-99"), 1);
+        assert_eq!(count("phone:9999"), 1);
+        assert_eq!(count("phone:0000"), 0);
+        assert_eq!(sql("phone:06.12").1, vec![text("%0612%"), text("%0612%")]);
+    }
+
+    #[test]
+    fn compiled_sql_runs_against_the_orders_view() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE active_orders (
+               id INTEGER PRIMARY KEY, reference TEXT, client_name TEXT, phone TEXT, phone_raw TEXT,
+               city TEXT, address TEXT, article_name TEXT, tracking_number TEXT, delivery_company TEXT,
+               status TEXT, done INTEGER, delivery_date TEXT, created_at TEXT);
+             CREATE TABLE order_items (order_id INTEGER, article_name TEXT);",
+        )
+        .unwrap();
+        let (where_sql, args) = sql(
+            r#"(status:open OR is:overdue) -is:done company:am* city:rabat due:..today+3
+               created:2026-01-01.. article:"mug*" client:ben phone:0612 ref:ORD* tracking:X1 "free text""#,
+        );
+        let n: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM active_orders o WHERE {where_sql}"),
+                params_from_iter(args.iter()),
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(n, 0);
+    }
+}
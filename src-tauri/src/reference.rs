@@ -0,0 +1,175 @@
+// src/reference.rs
+// Order references given to clients and couriers instead of the row id,
+// e.g. "ORD-2026-00042". A pattern is free text with placeholders:
+//   {YYYY} or {YY}   year the order was created
+//   {SEQ} or {SEQ:n} sequence within that year, zero-padded to n digits
+//   {CHECK}          Luhn check digit over the year and sequence
+
+/// Used when the `orderReferencePattern` setting is missing or invalid.
+pub const FALLBACK_REFERENCE_PATTERN: &str = "ORD-{YYYY}-{SEQ:5}";
+
+const DEFAULT_SEQ_WIDTH: usize = 4;
+const MAX_SEQ_WIDTH: usize = 9;
+const MAX_PATTERN_LEN: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    Year4,
+    Year2,
+    Seq(usize),
+    Check,
+}
+
+fn placeholder(name: &str) -> Option<Part<'_>> {
+    match name {
+        "YYYY" => Some(Part::Year4),
+        "YY" => Some(Part::Year2),
+        "SEQ" => Some(Part::Seq(DEFAULT_SEQ_WIDTH)),
+        "CHECK" => Some(Part::Check),
+        _ => {
+            let width = name.strip_prefix("SEQ:")?.parse::<usize>().ok()?;
+            (1..=MAX_SEQ_WIDTH).contains(&width).then_some(Part::Seq(width))
+        }
+    }
+}
+
+// Unknown placeholders stay as literal text; `validate_reference_pattern` rejects them
+fn parts(pattern: &str) -> Vec<Part<'_>> {
+    let mut out = Vec::new();
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        let Some(len) = rest[open..].find('}') else { break };
+        if open > 0 {
+            out.push(Part::Text(&rest[..open]));
+        }
+        let name = &rest[open..open + len + 1];
+        out.push(placeholder(&name[1..len]).unwrap_or(Part::Text(name)));
+        rest = &rest[open + len + 1..];
+    }
+    if !rest.is_empty() {
+        out.push(Part::Text(rest));
+    }
+    out
+}
+
+/// A pattern needs a year and exactly one sequence, and only known placeholders.
+pub fn validate_reference_pattern(pattern: &str) -> Result<(), String> {
+    if pattern.trim().is_empty() || pattern.len() > MAX_PATTERN_LEN {
+        return Err(format!("Reference pattern must be 1 to {MAX_PATTERN_LEN} characters"));
+    }
+    let parts = parts(pattern);
+    if let Some(Part::Text(bad)) = parts.iter().find(|p| matches!(p, Part::Text(t) if t.contains(['{', '}']))) {
+        return Err(format!("Unknown placeholder in reference pattern: '{bad}'"));
+    }
+    if !pattern.chars().all(|c| c.is_ascii_alphanumeric() || "-_/.#{}:".contains(c)) {
+        return Err("Reference pattern may only use letters, digits and - _ / . #".to_string());
+    }
+    if parts.iter().filter(|p| matches!(p, Part::Seq(_))).count() != 1 {
+        return Err("Reference pattern needs exactly one {SEQ} placeholder".to_string());
+    }
+    if !parts.iter().any(|p| matches!(p, Part::Year4 | Part::Year2)) {
+        return Err("Reference pattern needs a {YYYY} or {YY} placeholder".to_string());
+    }
+    Ok(())
+}
+
+// Luhn digit that makes `digits` + check valid
+fn luhn_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { let d2 = d * 2; if d2 > 9 { d2 - 9 } else { d2 } } else { d })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Render a reference; sequences wider than the padding are kept whole.
+pub fn render_reference(pattern: &str, year: i32, seq: i64) -> String {
+    let mut out = String::new();
+    for part in parts(pattern) {
+        match part {
+            Part::Text(t) => out.push_str(t),
+            Part::Year4 => out.push_str(&format!("{year:04}")),
+            Part::Year2 => out.push_str(&format!("{:02}", year.rem_euclid(100))),
+            Part::Seq(width) => out.push_str(&format!("{seq:0width$}")),
+            Part::Check => {
+                let digit = luhn_check_digit(&format!("{year:04}{seq}"));
+                out.push(char::from_digit(digit, 10).unwrap_or('0'));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_split_text_and_placeholders() {
+        assert_eq!(
+            parts("R{YY}-{SEQ:3}{CHECK}"),
+            vec![Part::Text("R"), Part::Year2, Part::Text("-"), Part::Seq(3), Part::Check]
+        );
+        assert_eq!(parts("{YYYY}{SEQ}"), vec![Part::Year4, Part::Seq(DEFAULT_SEQ_WIDTH)]);
+        // Unknown or out-of-range placeholders and unclosed braces stay literal
+        assert_eq!(parts("{FOO}{SEQ:0}"), vec![Part::Text("{FOO}"), Part::Text("{SEQ:0}")]);
+        assert_eq!(parts("A{YYYY"), vec![Part::Text("A{YYYY")]);
+    }
+
+    #[test]
+    fn validation_accepts_the_fallback_and_common_patterns() {
+        for p in [FALLBACK_REFERENCE_PATTERN, "{YY}{SEQ}", "INV/{YYYY}/{SEQ:9}-{CHECK}", "#{YY}.{SEQ:1}_x"] {
+            assert_eq!(validate_reference_pattern(p), Ok(()), "{p}");
+        }
+    }
+
+    #[test]
+    fn validation_rejects_bad_patterns() {
+        let err = |p: &str| validate_reference_pattern(p).unwrap_err();
+        assert_eq!(err("  "), "Reference pattern must be 1 to 40 characters");
+        assert_eq!(err(&format!("{{YYYY}}{{SEQ}}{}", "x".repeat(30))), "Reference pattern must be 1 to 40 characters");
+        assert_eq!(err("{YYYY}-{SEQ:10}"), "Unknown placeholder in reference pattern: '{SEQ:10}'");
+        assert_eq!(err("{yyyy}{SEQ}"), "Unknown placeholder in reference pattern: '{yyyy}'");
+        assert_eq!(err("{YYYY}{SEQ"), "Unknown placeholder in reference pattern: '{SEQ'");
+        assert_eq!(err("ORD {YYYY}{SEQ}"), "Reference pattern may only use letters, digits and - _ / . #");
+        assert_eq!(err("{YYYY}"), "Reference pattern needs exactly one {SEQ} placeholder");
+        assert_eq!(err("{YY}{SEQ}{SEQ:2}"), "Reference pattern needs exactly one {SEQ} placeholder");
+        assert_eq!(err("ORD-{SEQ}"), "Reference pattern needs a {YYYY} or {YY} placeholder");
+    }
+
+    #[test]
+    fn sequence_is_padded_but_never_truncated() {
+        assert_eq!(render_reference(FALLBACK_REFERENCE_PATTERN, 2026, 42), "ORD-2026-00042");
+        assert_eq!(render_reference("{YYYY}-{SEQ}", 2026, 7), "2026-0007");
+        assert_eq!(render_reference("{YYYY}-{SEQ:2}", 2026, 123), "2026-123");
+        assert_eq!(render_reference("{YYYY}-{SEQ:1}", 2026, 1), "2026-1");
+    }
+
+    #[test]
+    fn years_render_with_four_or_two_digits() {
+        assert_eq!(render_reference("{YYYY}/{YY}-{SEQ:1}", 2005, 1), "2005/05-1");
+        assert_eq!(render_reference("{YY}{SEQ:1}", 2100, 1), "001");
+        assert_eq!(render_reference("{YYYY}{SEQ:1}", 999, 1), "09991");
+    }
+
+    #[test]
+    fn luhn_check_digit_matches_the_reference_vector() {
+        // Classic example: 7992739871 is completed with 3
+        assert_eq!(luhn_check_digit("7992739871"), 3);
+        assert_eq!(luhn_check_digit("0"), 0);
+        assert_eq!(luhn_check_digit("20267"), 1);
+        assert_eq!(render_reference("INV{YY}{SEQ}{CHECK}", 2026, 7), "INV2600071");
+    }
+
+    #[test]
+    fn check_digit_catches_single_digit_and_adjacent_swap_errors() {
+        let valid = |s: &str| luhn_check_digit(&s[..s.len() - 1]) == s[s.len() - 1..].parse::<u32>().unwrap();
+        assert!(valid("79927398713"));
+        assert!(!valid("79927398712"));
+        assert!(!valid("79927398173")); // 7 and 1 swapped
+    }
+}
@@ -6,3 +6,117 @@ pub fn escape_like(input: &str) -> String {
         .replace('%', r#"\%"#)
         .replace('_', r#"\_"#)
 }
+
+// Identity key for a phone number: digits only, keeping a leading '+'
+pub fn phone_key(input: &str) -> String {
+    let trimmed = input.trim();
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return String::new();
+    }
+    if trimmed.starts_with('+') {
+        format!("+{digits}")
+    } else {
+        digits
+    }
+}
+
+// One CSV field: quoted (with doubled quotes) only when it needs to be
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Placeholder in a courier's tracking URL template
+pub const TRACKING_PLACEHOLDER: &str = "{tracking}";
+
+// Fill a tracking URL template; the number is percent-encoded so it cannot break the URL
+pub fn render_tracking_url(template: &str, tracking_number: &str) -> String {
+    let mut encoded = String::with_capacity(tracking_number.len());
+    for b in tracking_number.trim().bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+    template.replace(TRACKING_PLACEHOLDER, &encoded)
+}
+
+// Lower-case, accent-free form of a text for fuzzy comparisons ("Été" -> "ete")
+pub fn fold_text(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => out.push('a'),
+            'ç' | 'ć' | 'č' => out.push('c'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' => out.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => out.push('i'),
+            'ñ' | 'ń' => out.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => out.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' => out.push('u'),
+            'ý' | 'ÿ' => out.push('y'),
+            'š' | 'ś' => out.push('s'),
+            'ž' | 'ź' | 'ż' => out.push('z'),
+            'œ' => out.push_str("oe"),
+            'æ' => out.push_str("ae"),
+            'ß' => out.push_str("ss"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Strict yyyy-mm-dd calendar date
+pub fn is_valid_ymd(value: &str) -> bool {
+    let b = value.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+    let (Ok(y), Ok(m), Ok(d)) = (value[..4].parse::<u32>(), value[5..7].parse::<u32>(), value[8..].parse::<u32>()) else {
+        return false;
+    };
+    let leap = (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let days = match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phone_key_keeps_digits_and_a_leading_plus() {
+        assert_eq!(phone_key(" +212 (6) 12-34.56 "), "+2126123456");
+        assert_eq!(phone_key("06 12 34 56 78"), "0612345678");
+        assert_eq!(phone_key("tel: 0612"), "0612");
+        assert_eq!(phone_key("12+34"), "1234");
+        assert_eq!(phone_key("+"), "");
+        assert_eq!(phone_key("  "), "");
+    }
+
+    #[test]
+    fn tracking_url_percent_encodes_the_number() {
+        assert_eq!(render_tracking_url("https://x/t?n={tracking}", " AB 12/3 "), "https://x/t?n=AB%2012%2F3");
+        assert_eq!(render_tracking_url("https://x/{tracking}", "a-b_c.d~e"), "https://x/a-b_c.d~e");
+        assert_eq!(render_tracking_url("https://x/{tracking}", "é&?"), "https://x/%C3%A9%26%3F");
+        assert_eq!(render_tracking_url("https://x/track", "AB1"), "https://x/track");
+    }
+
+    #[test]
+    fn fold_text_lowercases_and_strips_accents() {
+        assert_eq!(fold_text("Été"), "ete");
+        assert_eq!(fold_text("ÀÉÎÕÜ Ça"), "aeiou ca");
+        assert_eq!(fold_text("Œuvre Straße"), "oeuvre strasse");
+        assert_eq!(fold_text("T-Shirt 42"), "t-shirt 42");
+        assert_eq!(fold_text(""), "");
+    }
+}